@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::validation::validate_pubkey;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// root node of the graph holding the address book, shared by all flow graphs
+pub const ADDRESS_BOOK_MARKER: &str = "ADDRESS_BOOK_MARKER";
+pub const ADDRESS_BOOK_ENTRIES: &str = "entries";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AddressEntry {
+    pub name: String,
+    pub pubkey: String, // used when the network has no value of its own
+    #[serde(default)]
+    pub networks: HashMap<String, String>, // SolanaNet::name() -> pubkey
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl AddressEntry {
+    pub fn pubkey(&self, network: &str) -> &str {
+        self.networks
+            .get(network)
+            .map(String::as_str)
+            .unwrap_or(&self.pubkey)
+    }
+}
+
+/// named pubkeys, e.g. treasury or collection, referenced by "Address" consts
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct AddressBook {
+    entries: BTreeMap<String, AddressEntry>,
+}
+
+impl AddressBook {
+    /// entries stored on the node of the address book graph
+    pub fn from_properties(properties: &Properties) -> Self {
+        properties
+            .get(ADDRESS_BOOK_ENTRIES)
+            .and_then(|entries| serde_json::from_value(entries.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &AddressEntry> {
+        self.entries.values()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AddressEntry> {
+        self.entries.get(name.trim())
+    }
+
+    /// add or replace an entry with the same name
+    pub fn insert(&mut self, mut entry: AddressEntry) -> Result<(), String> {
+        entry.name = entry.name.trim().to_owned();
+        if entry.name.is_empty() {
+            return Err("address name is empty".to_owned());
+        }
+
+        validate_pubkey(&entry.pubkey).map_err(|e| format!("{}: {}", entry.name, e))?;
+        for (network, pubkey) in entry.networks.iter() {
+            validate_pubkey(pubkey).map_err(|e| format!("{} on {}: {}", entry.name, network, e))?;
+        }
+
+        self.entries.insert(entry.name.clone(), entry);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<AddressEntry> {
+        self.entries.remove(name.trim())
+    }
+
+    pub fn resolve(&self, name: &str, network: &str) -> Result<String, String> {
+        self.get(name)
+            .map(|entry| entry.pubkey(network).to_owned())
+            .ok_or_else(|| format!("address {} not found in address book", name.trim()))
+    }
+}
+
+#[test]
+fn address_book_resolves_per_network() {
+    let mut book = AddressBook::default();
+    book.insert(AddressEntry {
+        name: " treasury ".into(),
+        pubkey: "11111111111111111111111111111111".into(),
+        networks: HashMap::from([(
+            "mainnet".to_owned(),
+            "SysvarRent111111111111111111111111111111111".to_owned(),
+        )]),
+        notes: String::new(),
+    })
+    .unwrap();
+
+    assert_eq!(
+        book.resolve("treasury", "devnet").unwrap(),
+        "11111111111111111111111111111111"
+    );
+    assert_eq!(
+        book.resolve("treasury", "mainnet").unwrap(),
+        "SysvarRent111111111111111111111111111111111"
+    );
+    assert!(book.resolve("creator", "devnet").is_err());
+    assert!(book
+        .insert(AddressEntry {
+            name: "bad".into(),
+            pubkey: "not a key".into(),
+            networks: HashMap::new(),
+            notes: String::new(),
+        })
+        .is_err());
+}
@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::command::commands_map;
+use crate::review::{number, AMOUNT_INPUTS};
+use crate::run_outputs;
+use crate::validation::const_payload;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+// output of the commands sending a transaction
+const SIGNATURE_OUTPUT: &str = "signature";
+
+/// one on-chain transaction made by a flow run
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AuditEntry {
+    pub timestamp: String, // rfc3339
+    pub graph_id: String,
+    pub graph_name: String,
+    pub node_id: String,
+    pub command_name: String,
+    pub network: String,
+    pub signature: String,
+    pub signers: Vec<String>,
+    pub amounts: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditFilter {
+    #[serde(default)]
+    pub graph_id: Option<String>,
+    #[serde(default)]
+    pub from: Option<String>, // rfc3339, inclusive
+    #[serde(default)]
+    pub to: Option<String>, // rfc3339, exclusive
+}
+
+/// append-only ledger, one json entry per line next to the run logs
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    signatures: HashSet<String>, // already recorded, run logs are polled again and again
+}
+
+impl AuditLog {
+    pub fn open(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_owned();
+        let signatures = read_entries(&path)
+            .into_iter()
+            .map(|entry| entry.signature)
+            .collect();
+
+        Self { path, signatures }
+    }
+
+    pub fn contains(&self, signature: &str) -> bool {
+        self.signatures.contains(signature)
+    }
+
+    /// returns false if the transaction was already recorded
+    pub fn record(&mut self, entry: AuditEntry) -> std::io::Result<bool> {
+        if self.contains(&entry.signature) {
+            return Ok(false);
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry).unwrap())?;
+
+        self.signatures.insert(entry.signature);
+        Ok(true)
+    }
+
+    pub fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>, String> {
+        let parse = |date: &Option<String>| {
+            date.as_deref()
+                .map(|date| {
+                    DateTime::parse_from_rfc3339(date)
+                        .map(|date| date.with_timezone(&Utc))
+                        .map_err(|e| format!("invalid date {}: {}", date, e))
+                })
+                .transpose()
+        };
+        let from = parse(&filter.from)?;
+        let to = parse(&filter.to)?;
+
+        Ok(read_entries(&self.path)
+            .into_iter()
+            .filter(|entry| {
+                filter
+                    .graph_id
+                    .iter()
+                    .all(|graph_id| &entry.graph_id == graph_id)
+            })
+            .filter(|entry| {
+                let timestamp = match DateTime::parse_from_rfc3339(&entry.timestamp) {
+                    Ok(timestamp) => timestamp.with_timezone(&Utc),
+                    Err(_) => return false,
+                };
+                from.iter().all(|from| timestamp >= *from) && to.iter().all(|to| timestamp < *to)
+            })
+            .collect())
+    }
+}
+
+fn read_entries(path: &Path) -> Vec<AuditEntry> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// signature, signers and amounts of a node as its command declares them:
+/// the signature output, the keypairs given to Keypair inputs and the amount inputs
+pub fn transaction_outputs(
+    command_name: &str,
+    inputs: &Properties,
+    outputs: &Properties,
+) -> Option<(String, Vec<String>, BTreeMap<String, f64>)> {
+    let command = commands_map().get(command_name).copied()?;
+    if !command
+        .outputs()
+        .iter()
+        .any(|output| output.name == SIGNATURE_OUTPUT)
+    {
+        return None;
+    }
+    let signature = const_payload(outputs.get(SIGNATURE_OUTPUT)?)
+        .as_str()?
+        .to_owned();
+
+    // a pubkey given to a "Keypair, Pubkey" input doesn't sign
+    let mut signers: Vec<String> = command
+        .inputs()
+        .iter()
+        .filter(|input| input.acceptable_types().contains("Keypair"))
+        .filter_map(|input| inputs.get(input.name))
+        .filter(|value| value.get("Keypair").is_some())
+        .filter_map(|value| {
+            run_outputs::redact(value)
+                .get("Pubkey")?
+                .as_str()
+                .map(str::to_owned)
+        })
+        .collect();
+    signers.sort();
+    signers.dedup();
+
+    let amounts = command
+        .inputs()
+        .iter()
+        .filter(|input| AMOUNT_INPUTS.contains(&(command_name, input.name)))
+        .filter_map(|input| Some((input.name.to_owned(), number(inputs.get(input.name)?)?)))
+        .collect();
+
+    Some((signature, signers, amounts))
+}
+
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let escape = |field: &str| {
+        if field.contains(',') || field.contains('"') || field.contains('\n') {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    };
+
+    let mut csv = String::from(
+        "timestamp,graph_id,graph_name,node_id,command,network,signature,signers,amounts\n",
+    );
+    for entry in entries {
+        let amounts: Vec<String> = entry
+            .amounts
+            .iter()
+            .map(|(key, amount)| format!("{}={}", key, amount))
+            .collect();
+        let fields = [
+            entry.timestamp.clone(),
+            entry.graph_id.clone(),
+            entry.graph_name.clone(),
+            entry.node_id.clone(),
+            entry.command_name.clone(),
+            entry.network.clone(),
+            entry.signature.clone(),
+            entry.signers.join(";"),
+            amounts.join(";"),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| escape(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[test]
+fn audit_log_records_once_and_filters() {
+    let inputs = serde_json::json!({
+        "sender": { "Keypair": bs58::encode([1u8; 64]).into_string() },
+        "recipient": { "Pubkey": bs58::encode([2u8; 32]).into_string() },
+        "amount": { "F64": 1.5 },
+    });
+    let outputs = serde_json::json!({ "signature": { "String": "5abc" } });
+    let (signature, signers, amounts) = transaction_outputs(
+        "transfer_solana",
+        inputs.as_object().unwrap(),
+        outputs.as_object().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(signature, "5abc");
+    assert_eq!(signers, vec![bs58::encode([1u8; 32]).into_string()]);
+    assert_eq!(amounts.len(), 1);
+    assert_eq!(amounts["amount"], 1.5);
+
+    // the output of a command sending no transaction
+    assert!(
+        transaction_outputs("print", &Properties::new(), outputs.as_object().unwrap()).is_none()
+    );
+
+    let path = std::env::temp_dir().join(format!("audit-{}.jsonl", uuid::Uuid::new_v4()));
+    let mut audit_log = AuditLog::open(&path);
+    let entry = AuditEntry {
+        timestamp: "2022-03-01T10:00:00Z".into(),
+        graph_id: "graph".into(),
+        graph_name: "mint, then transfer".into(),
+        node_id: "node".into(),
+        command_name: "transfer_solana".into(),
+        network: "devnet".into(),
+        signature,
+        signers,
+        amounts,
+    };
+    assert!(audit_log.record(entry.clone()).unwrap());
+    assert!(!AuditLog::open(&path).record(entry).unwrap());
+
+    let filter = AuditFilter {
+        graph_id: Some("graph".into()),
+        from: Some("2022-03-01T00:00:00Z".into()),
+        to: Some("2022-03-02T00:00:00Z".into()),
+    };
+    let entries = audit_log.query(&filter).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(to_csv(&entries).contains("\"mint, then transfer\""));
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -1,6 +1,8 @@
 use std::cmp;
 use std::collections::{HashMap, HashSet};
 
+use serde_json::Value as JsonValue;
+
 use sunshine_solana::commands::simple::branch;
 use sunshine_solana::commands::simple::http_request;
 use sunshine_solana::commands::simple::ipfs_nft_upload;
@@ -70,6 +72,7 @@ pub const COMMANDS: &'static [&'static dyn DynCommand] = &[
     &IpfsNftUploadCommand,
     &WaitCommand,
     &BranchCommand,
+    &WatchTriggerCommand,
     // Solana
     &CreateMintAccountCommand,
     // &AddPubkeyCommand,
@@ -189,6 +192,94 @@ pub struct TypeBound {
     pub types: &'static [&'static str],
 }
 
+// type of a serialized sunshine_solana::Value, e.g. { "Pubkey": "..." } or "Empty"
+fn value_type_name(value: &JsonValue) -> Option<&str> {
+    match value {
+        JsonValue::Object(map) if map.len() == 1 => map.keys().next().map(String::as_str),
+        JsonValue::String(unit_variant) => Some(unit_variant),
+        _ => None,
+    }
+}
+
+// value variants and type bounds don't use the same names
+fn normalize_type_name(name: &str) -> String {
+    let name = name.replace('_', "").to_lowercase();
+    match name.as_str() {
+        "integer" | "balance" => "number".to_owned(),
+        "metadataaccountdata" => "metadataaccount".to_owned(),
+        _ => name,
+    }
+}
+
+/// check a serialized value against the acceptable types of an input,
+/// joined with ", " as in InputModel::type_bounds
+pub fn literal_matches_type_bounds(literal: &JsonValue, type_bounds: &str) -> bool {
+    let acceptable_types: Vec<String> = type_bounds
+        .split(", ")
+        .filter(|name| !name.is_empty())
+        .map(normalize_type_name)
+        .collect();
+
+    if acceptable_types.is_empty() {
+        return true;
+    }
+
+    value_type_name(literal).map_or(false, |name| {
+        acceptable_types.contains(&normalize_type_name(name))
+    })
+}
+
+#[test]
+fn literal_type_bounds() {
+    let pubkey = serde_json::json!({ "Pubkey": "11111111111111111111111111111111" });
+    let integer = serde_json::json!({ "Integer": 5 });
+
+    assert!(literal_matches_type_bounds(&pubkey, "Keypair, Pubkey"));
+    assert!(!literal_matches_type_bounds(&pubkey, "String"));
+    assert!(literal_matches_type_bounds(&integer, "Number"));
+    assert!(literal_matches_type_bounds(&integer, ""));
+}
+
+// inputs taking a secret as a plain string
+const SECRET_INPUTS: &[(&str, &str)] = &[
+    ("generate_keypair", "private_key"),
+    ("generate_keypair", "seed_phrase"),
+    ("generate_keypair", "passphrase"),
+];
+
+/// whether the literal of an input is a secret, e.g. a keypair or a seed phrase
+pub fn is_secret_literal(command_name: &str, input_name: &str, literal: &JsonValue) -> bool {
+    let keypair_only = commands_map()
+        .get(command_name)
+        .and_then(|command| {
+            command
+                .inputs()
+                .iter()
+                .find(|input| input.name == input_name)
+        })
+        .map_or(false, |input| {
+            input.acceptable_types().into_iter().eq(["Keypair"])
+        });
+
+    keypair_only
+        || value_type_name(literal) == Some("Keypair")
+        || SECRET_INPUTS.contains(&(command_name, input_name))
+}
+
+#[test]
+fn secret_literals() {
+    let keypair = serde_json::json!({ "Keypair": "4wBqpZM9xaSheZzJSMawUKKwhdpChKbZ5eu5ky4Vigw" });
+    let string = serde_json::json!({ "String": "word word word" });
+
+    assert!(is_secret_literal("print", "print", &keypair));
+    assert!(is_secret_literal(
+        "generate_keypair",
+        "seed_phrase",
+        &string
+    ));
+    assert!(!is_secret_literal("print", "print", &string));
+}
+
 pub trait Command {
     const COMMAND_NAME: &'static str;
     const DESCRIPTION: &'static str;
@@ -209,6 +300,10 @@ pub trait DynCommand: std::fmt::Debug {
     fn availability(&self) -> &'static [SolanaNet];
     fn dimensions(&self) -> NodeDimensions; // TODO: Move to DynCommandView
     fn config(&self) -> CommandConfig;
+
+    fn is_available(&self, solana_net: SolanaNet) -> bool {
+        self.availability().contains(&solana_net.availability_net())
+    }
 }
 
 impl<T: Command + std::fmt::Debug> DynCommand for T {
@@ -273,6 +368,9 @@ pub struct BranchCommand;
 #[derive(Copy, Clone, Debug)]
 pub struct WaitCommand;
 
+#[derive(Copy, Clone, Debug)]
+pub struct WatchTriggerCommand;
+
 // SOLANA
 
 #[derive(Copy, Clone, Debug)]
@@ -741,6 +839,40 @@ impl Command for WaitCommand {
         &[SolanaNet::Devnet, SolanaNet::Testnet, SolanaNet::Mainnet];
 }
 
+// Starts a run per file of the directory of the graph's WatchTriggerConfig,
+// not run by the runner, its outputs are given to the nodes it's connected to
+impl Command for WatchTriggerCommand {
+    const COMMAND_NAME: &'static str = "watch_trigger";
+    const WIDGET_NAME: &'static str = "WatchTrigger";
+    const INPUTS: &'static [CommandInput] = &[];
+    const OUTPUTS: &'static [CommandOutput] = &[
+        CommandOutput::new("path", "String", &false, "path of the new or changed file"),
+        CommandOutput::new(
+            "contents",
+            "String",
+            &false,
+            "contents of the file, none for binary files",
+        ),
+    ];
+    fn dimensions() -> NodeDimensions {
+        NodeDimensions {
+            height: calculate_node_height(Self),
+            width: 300,
+        }
+    }
+    fn config() -> CommandConfig {
+        // never deployed, see run_copy.rs
+        CommandConfig::Simple(SimpleCommand::Const(sunshine_solana::Value::String(
+            "".to_string(),
+        )))
+    }
+    const DESCRIPTION: &'static str =
+        "Runs the flow for each new or changed file of the watched directory";
+
+    const AVAILABILITY: &'static [SolanaNet] =
+        &[SolanaNet::Devnet, SolanaNet::Testnet, SolanaNet::Mainnet];
+}
+
 // Creates a mint account for a custom token
 impl Command for CreateMintAccountCommand {
     const COMMAND_NAME: &'static str = "create_mint_account";
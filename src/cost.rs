@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+// rent is charged on the account data plus this overhead
+const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// fees and account sizes used to estimate what a flow costs,
+/// values are those of mainnet and can be overridden with a json file
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FeeTable {
+    pub lamports_per_signature: u64,
+    pub lamports_per_byte_year: u64,
+    pub exemption_threshold_years: u64,
+    pub commands: HashMap<String, CommandCost>, // by command name
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CommandCost {
+    pub signatures: u64,
+    #[serde(default)]
+    pub account_sizes: Vec<u64>, // data length of each account created
+}
+
+impl Default for FeeTable {
+    fn default() -> Self {
+        let cost = |signatures, account_sizes: &[u64]| CommandCost {
+            signatures,
+            account_sizes: account_sizes.to_vec(),
+        };
+
+        let commands = [
+            ("create_mint_account", cost(2, &[82])),
+            ("create_token_account", cost(1, &[165])),
+            ("mint_token", cost(2, &[])),
+            ("transfer_token", cost(2, &[165])), // may create the recipient account
+            ("transfer_solana", cost(1, &[])),
+            ("create_metadata_accounts", cost(2, &[679])),
+            ("create_master_edition", cost(2, &[282])),
+            ("update_metadata_accounts", cost(1, &[])),
+            ("verify_collection", cost(2, &[])),
+            ("sign_metadata", cost(1, &[])),
+            ("approve_collection_authority", cost(2, &[11])),
+            ("utilize", cost(2, &[])),
+            ("approve_use_authority", cost(2, &[10])),
+            ("create_auction_house", cost(2, &[459])),
+            ("auction_house_sell", cost(2, &[33])),
+            ("arweave_nft_upload", cost(1, &[])),
+            ("arweave_file_upload", cost(1, &[])),
+        ];
+
+        Self {
+            lamports_per_signature: 5000,
+            lamports_per_byte_year: 3480,
+            exemption_threshold_years: 2,
+            commands: commands
+                .into_iter()
+                .map(|(name, cost)| (name.to_owned(), cost))
+                .collect(),
+        }
+    }
+}
+
+impl FeeTable {
+    /// default table when the file doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn rent_exempt_minimum(&self, data_len: u64) -> u64 {
+        (data_len + ACCOUNT_STORAGE_OVERHEAD)
+            * self.lamports_per_byte_year
+            * self.exemption_threshold_years
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeCost {
+    pub node_id: String,
+    pub command_name: String,
+    pub fee_lamports: u64,
+    pub rent_lamports: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CostEstimate {
+    pub nodes: Vec<NodeCost>,
+    pub total_lamports: u64,
+    pub total_sol: f64,
+}
+
+/// commands missing from the table are free, e.g. const or print
+pub fn estimate<'a>(
+    commands: impl Iterator<Item = (String, &'a str)>, // node id, command name
+    fee_table: &FeeTable,
+) -> CostEstimate {
+    let mut estimate = CostEstimate::default();
+
+    for (node_id, command_name) in commands {
+        let cost = match fee_table.commands.get(command_name) {
+            Some(cost) => cost,
+            None => continue,
+        };
+
+        let fee_lamports = cost.signatures * fee_table.lamports_per_signature;
+        let rent_lamports = cost
+            .account_sizes
+            .iter()
+            .map(|data_len| fee_table.rent_exempt_minimum(*data_len))
+            .sum();
+
+        estimate.total_lamports += fee_lamports + rent_lamports;
+        estimate.nodes.push(NodeCost {
+            node_id,
+            command_name: command_name.to_owned(),
+            fee_lamports,
+            rent_lamports,
+        });
+    }
+
+    estimate.nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    estimate.total_sol = estimate.total_lamports as f64 / LAMPORTS_PER_SOL as f64;
+    estimate
+}
+
+#[test]
+fn estimate_mint_costs() {
+    let fee_table = FeeTable::default();
+
+    // rent of a mint account on mainnet
+    assert_eq!(fee_table.rent_exempt_minimum(82), 1_461_600);
+
+    let commands = vec![
+        ("a".to_owned(), "create_mint_account"),
+        ("b".to_owned(), "const"),
+        ("c".to_owned(), "transfer_solana"),
+    ];
+    let estimate = estimate(commands.into_iter(), &fee_table);
+
+    assert_eq!(estimate.nodes.len(), 2);
+    assert_eq!(estimate.nodes[0].fee_lamports, 10_000);
+    assert_eq!(estimate.total_lamports, 10_000 + 1_461_600 + 5000);
+}
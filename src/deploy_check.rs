@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::flow_graph::FlowGraph;
+use crate::flow_input;
+use crate::model::{DeployError, NodeId, REDACTED_PLACEHOLDER, SECRET_MARKER};
+use crate::review::deploy_review;
+use crate::validation::{
+    const_text_payload, is_redacted_literal, is_redacted_text, is_secret_kind, validate_const,
+};
+use crate::vault::Vault;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// checks a flow before it's deployed, as stored in the db,
+/// returns the texts of the flow inputs given at deploy time
+pub fn check_deploy(
+    flow: &FlowGraph,
+    input_values: &Properties,
+    vault: &Vault,
+) -> Result<HashMap<NodeId, String>, DeployError> {
+    let redacted_node_ids: Vec<NodeId> = flow
+        .nodes
+        .keys()
+        .filter(|node_id| {
+            let is_redacted_const = flow.command_name(**node_id) == Some("const")
+                && is_redacted_text(flow.text(**node_id));
+            is_redacted_const
+                || flow
+                    .literals(**node_id)
+                    .values()
+                    .any(|literal| is_redacted_literal(literal))
+        })
+        .copied()
+        .collect();
+    if !redacted_node_ids.is_empty() {
+        return Err(DeployError::Redacted(redacted_node_ids));
+    }
+
+    // TODO: the runner builds its rpc client from solana::Config, which only knows public
+    // clusters, pass the endpoint once it takes one
+    if flow.solana_endpoint().is_some() {
+        return Err(DeployError::Endpoint(flow.solana_net()));
+    }
+
+    let solana_net = flow.solana_net();
+    let missing_node_ids: Vec<NodeId> = flow
+        .nodes
+        .keys()
+        .filter(|node_id| flow.is_missing_network_value(**node_id, solana_net))
+        .copied()
+        .collect();
+    if !missing_node_ids.is_empty() {
+        return Err(DeployError::MissingNetworkValue(missing_node_ids));
+    }
+
+    let input_texts = flow_input_texts(flow, input_values)?;
+
+    deploy_review(flow, &input_texts, vault)
+        .check_spending_cap()
+        .map_err(DeployError::SpendingCap)?;
+
+    Ok(input_texts)
+}
+
+/// const text of each parameter given at deploy time, checked like the canvas value
+pub fn flow_input_texts(
+    flow: &FlowGraph,
+    input_values: &Properties,
+) -> Result<HashMap<NodeId, String>, DeployError> {
+    let inputs = flow.flow_inputs();
+
+    if let Some(name) = input_values
+        .keys()
+        .find(|name| !inputs.iter().any(|input| &input.name == *name))
+    {
+        return Err(DeployError::FlowInput(format!(
+            "unknown flow input {}",
+            name
+        )));
+    }
+
+    let mut texts = HashMap::new();
+    for input in inputs {
+        let node_id = NodeId(Uuid::parse_str(&input.node_id).unwrap());
+        let value = match input_values.get(&input.name) {
+            Some(value) => value,
+            None if input.default.is_some() => continue,
+            None => {
+                return Err(DeployError::FlowInput(format!(
+                    "flow input {} is required",
+                    input.name
+                )))
+            }
+        };
+
+        let text = flow_input::with_payload(flow.text(node_id), value)
+            .and_then(|text| {
+                validate_const(&input.kind, &text).map_err(|e| e.message)?;
+                Ok(text)
+            })
+            .map_err(|e| DeployError::FlowInput(format!("{}: {}", input.name, e)))?;
+        texts.insert(node_id, text);
+    }
+
+    Ok(texts)
+}
+
+/// values of the flow inputs for the run log, secrets are left out
+pub fn recorded_inputs(flow: &FlowGraph, input_texts: &HashMap<NodeId, String>) -> Properties {
+    flow.flow_inputs()
+        .into_iter()
+        .map(|input| {
+            let node_id = NodeId(Uuid::parse_str(&input.node_id).unwrap());
+            let is_secret =
+                flow.nodes[&node_id].contains_key(SECRET_MARKER) || is_secret_kind(&input.kind);
+            let value = if is_secret {
+                JsonValue::String(REDACTED_PLACEHOLDER.to_owned())
+            } else {
+                let text = input_texts
+                    .get(&node_id)
+                    .map(String::as_str)
+                    .unwrap_or_else(|| flow.text(node_id));
+                const_text_payload(text).unwrap_or(JsonValue::Null)
+            };
+            (input.name, value)
+        })
+        .collect()
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-use crate::model::{InputId, NodeId, OutputId, PortId};
+use crate::model::{EdgeId, InputId, NodeId, OutputId, PortId};
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Coords {
@@ -17,6 +17,9 @@ pub enum Event {
     CreateNode(Coords),
     EditNode(NodeId),
     RemoveNodes(HashSet<NodeId>),
+    SelectEdge(EdgeId),
+    AddOrRemoveEdgeToSelection(EdgeId),
+    RemoveEdges(HashSet<EdgeId>),
     //
     MaybeStartSelection(Coords),
     NotASelection,
@@ -46,6 +49,13 @@ pub enum Event {
     EndEdge(InputId, OutputId),
     CancelEdge(PortId),
     //
+    MaybeStartEdgeRewire(EdgeId, PortId), // edge, port that stays connected
+    NotAEdgeRewire,
+    StartEdgeRewire(EdgeId, PortId, Coords),
+    ContinueEdgeRewire(EdgeId, PortId, Coords),
+    EndEdgeRewire(EdgeId, InputId, OutputId),
+    CancelEdgeRewire(EdgeId),
+    //
     // x, y, multiplier
     ScrollZoom(f64, f64, f64),
     ScrollMoveScreen(f64, f64),
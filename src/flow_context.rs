@@ -1,27 +1,114 @@
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::model::{GraphEntry, GraphId, NodeId};
+use crate::address_book::AddressBook;
+use crate::audit::{transaction_outputs, AuditEntry, AuditLog};
+use crate::deploy_check::{check_deploy, recorded_inputs};
+use crate::flow_graph::FlowGraph;
+use crate::http_trigger::{self, HttpTriggerConfig, TriggerContext};
+use crate::model::{DeployError, GraphEntry, GraphId, NodeId};
+use crate::partial_run::{self, PartialRunPlan, PortEdge};
+use crate::review::sol_transferred;
+use crate::run_copy;
+use crate::run_outputs;
+use crate::vault::Vault;
+use crate::view::RunStateView;
+use crate::watch_trigger::{self, WatchTriggerConfig, WATCH_TRIGGER_COMMAND};
 use crate::Confirm;
 use dashmap::DashMap;
 use futures::executor::block_on;
 use std::sync::Mutex;
 use sunshine_core::msg::Action;
+use sunshine_core::msg::MutateKind;
 use sunshine_core::msg::QueryKind;
 use sunshine_core::store::Datastore;
 use sunshine_solana::FlowContext as InnerFlowContext;
 use sunshine_solana::RunState;
 use sunshine_solana::Schedule;
+use sunshine_solana::COMMAND_NAME_MARKER;
 use sunshine_solana::RUN_ID_MARKER;
 
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, Notify};
 use uuid::Uuid;
 
+type Properties = serde_json::Map<String, serde_json::Value>;
+
+// how often the log graph of a running flow is checked for new node states,
+// the runner has no callback for them and only writes them to the db
+// TODO: take node states from a channel once InnerFlowContext::deploy_flow accepts a sender
+const RUN_WATCH_INTERVAL: tokio::time::Duration = tokio::time::Duration::from_secs(1);
+
+// finished runs kept for their state and the run_state endpoint, oldest dropped first
+const MAX_FINISHED_RUNS: usize = 100;
+
 #[derive(Debug)]
 pub struct FlowContext {
     tx: mpsc::UnboundedSender<Packet>,
-    run_id: Arc<Mutex<Uuid>>, // TODO use run id?
+    runs: Arc<DashMap<Uuid, ActiveRun>>, // by run id, finished ones included
+    outputs: Arc<DashMap<NodeId, Properties>>, // of each node's latest successful run
+    run_finished: Arc<Notify>,
+}
+
+/// run of a graph, a graph can run several times at once
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveRun {
+    pub run_id: Uuid,
+    pub flow_id: GraphId,
+    pub started: u64,         // deploy order, the latest run shows on the nodes
+    pub network: String,      // SolanaNet::name(), for the audit log
+    pub state: RunStateView,  // Running, then Success, Failed or Canceled
+    pub inputs: Properties,   // flow input values the run was deployed with
+    pub deployed_id: GraphId, // the copy the runner runs, see run_copy.rs
+    pub node_ids: HashMap<NodeId, NodeId>, // copied node -> node of the graph
+    pub edges: Vec<PortEdge>, // of the copy, see run_outputs::captured_outputs
+    pub session: Option<Uuid>, // debug session the run is a segment of
+    pub command_names: HashMap<NodeId, String>, // by copied node
+    pub spending_cap: Option<f64>, // SOL, checked against the transfers of the run as it goes
+}
+
+impl ActiveRun {
+    /// node of the graph a log node ran for, None for the consts of literals and cached outputs
+    pub fn original_node_id(&self, node_id: NodeId) -> Option<NodeId> {
+        self.node_ids.get(&node_id).copied()
+    }
+}
+
+/// run of a graph, or of the nodes of a plan given the cached outputs of their upstream nodes,
+/// checked against the graph as stored in the db when deployed
+#[derive(Debug, Clone)]
+pub struct RunRequest {
+    pub flow_id: GraphId,
+    pub input_values: Properties, // flow inputs by name, missing ones use the canvas value
+    pub trigger_outputs: Properties, // of the watch_trigger node, for the file of the run
+    pub unattended: bool,         // nobody reviews what it signs, e.g. triggers
+    pub session: Option<Uuid>,    // debug session the run is a segment of
+    pub plan: Option<PartialRunPlan>, // None runs the whole graph
+}
+
+/// deploys runs from the runtime, for triggers
+#[derive(Debug, Clone)]
+pub struct RunSender(mpsc::UnboundedSender<Packet>);
+
+impl RunSender {
+    pub async fn deploy(&self, request: RunRequest) -> Result<Uuid, DeployError> {
+        let (tx, rx) = oneshot::channel();
+        let (result_tx, result_rx) = oneshot::channel();
+
+        self.0
+            .send(Packet {
+                cmd: Cmd::Deploy(request, result_tx),
+                res: tx,
+            })
+            .map_err(|_| DeployError::Runner("flow context stopped".to_owned()))?;
+
+        rx.await
+            .map_err(|_| DeployError::Runner("flow context stopped".to_owned()))?;
+        result_rx
+            .await
+            .map_err(|_| DeployError::Runner("flow context stopped".to_owned()))?
+    }
 }
 
 #[derive(Debug)]
@@ -30,10 +117,33 @@ struct Packet {
     res: oneshot::Sender<()>,
 }
 
+/// change reported while a flow runs
+#[derive(Debug)]
+enum RunEvent {
+    NodeState {
+        run_id: Uuid,
+        node_id: NodeId,
+        state: RunState,
+        print_output: Option<String>,
+        outputs: Properties,          // values of the outputs read so far
+        inputs: Properties,           // logged by the runner, for the audit log
+        succeeded_at: Option<String>, // rfc3339, first poll seeing the node succeed
+    },
+    Completed {
+        run_id: Uuid,
+        log_graph: Uuid,
+        timestamp: String,
+    },
+}
+
 #[derive(Debug)]
 enum Cmd {
-    Deploy(GraphId),
-    Undeploy(GraphId),
+    Deploy(RunRequest, oneshot::Sender<Result<Uuid, DeployError>>),
+    Finish(Uuid),    // the run is over, its copy isn't needed anymore
+    Cancel(GraphId), // every running run of the graph
+    CancelRun(Uuid), // a single run, e.g. over the spending cap
+    SetHttpTrigger(HttpTriggerConfig),
+    SetWatchTrigger(GraphId, Option<WatchTriggerConfig>),
     Stop,
 }
 
@@ -47,17 +157,33 @@ enum Cmd {
 impl FlowContext {
     pub fn new(
         db: Arc<dyn Datastore>,
-        run_status: Arc<DashMap<NodeId, (RunState, Option<String>)>>,
+        run_status: Arc<DashMap<NodeId, (RunState, Option<String>, Properties)>>,
         req_id: Arc<Mutex<u64>>,
-        graph_id: Arc<Mutex<GraphId>>,
         graph_entry: GraphEntry, //TODO remove
         log_path: String,
+        audit_log: Arc<Mutex<AuditLog>>,
+        vault: Arc<Mutex<Vault>>,
+        address_book_id: GraphId,
     ) -> FlowContext {
-        let run_id = Arc::new(Mutex::new(Uuid::new_v4()));
+        let runs = Arc::new(DashMap::new());
+        let outputs = Arc::new(DashMap::new());
+        let run_finished = Arc::new(Notify::new());
+        let waited_runs = run_finished.clone();
 
         let (tx, mut rx) = mpsc::unbounded_channel::<Packet>();
+        let finish_tx = tx.clone();
 
-        let run_id_mod = run_id.clone();
+        let trigger_ctx = TriggerContext {
+            db: db.clone(),
+            runs: runs.clone(),
+            sender: RunSender(tx.clone()),
+            run_finished: run_finished.clone(),
+            config: HttpTriggerConfig::default(),
+        };
+
+        let runs_mod = runs.clone();
+        let outputs_mod = outputs.clone();
+        let cached_outputs = outputs.clone();
 
         std::thread::spawn(move || {
             let threaded_rt = tokio::runtime::Builder::new_multi_thread() //TODO: fix limits for worker_threads
@@ -71,128 +197,274 @@ impl FlowContext {
 
             std::fs::create_dir(path).ok();
 
-            let current_run_id = run_id_mod.clone();
-
-            threaded_rt.spawn(async move {
-                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+            let current_runs = runs_mod.clone();
 
-                loop {
-                    interval.tick().await;
+            let (events_tx, mut events_rx) = mpsc::unbounded_channel::<RunEvent>();
+            let watch_db = db.clone();
+            let cancel_log_path = log_path.clone();
 
-                    let flow_node = {
-                        let graph_id = graph_id.lock().unwrap();
-                        block_on(db.execute(Action::Query(QueryKind::ReadNode(graph_id.0))))
-                            .unwrap()
-                            .into_node()
-                            .unwrap()
-                    };
+            threaded_rt.spawn(async move {
+                // SOL transferred by each run so far, and the nodes counted
+                let mut spent_sol: HashMap<Uuid, (f64, HashSet<NodeId>)> = HashMap::new();
 
-                    let mut changed = false;
+                while let Some(event) = events_rx.recv().await {
+                    match event {
+                        RunEvent::NodeState {
+                            run_id,
+                            node_id,
+                            state,
+                            print_output,
+                            outputs: node_outputs,
+                            inputs,
+                            succeeded_at,
+                        } => {
+                            // canceled since
+                            let run = match live_run(&current_runs, run_id) {
+                                Some(run) => run,
+                                None => {
+                                    spent_sol.remove(&run_id);
+                                    continue;
+                                }
+                            };
 
-                    let graph_name = flow_node
-                        .properties
-                        .get("name")
-                        .unwrap()
-                        .as_str()
-                        .unwrap()
-                        .to_owned();
+                            // the runner signs without knowing the spending cap,
+                            // the run is canceled once its transfers go over it
+                            if let Some(sol) = run
+                                .command_names
+                                .get(&node_id)
+                                .and_then(|command_name| sol_transferred(command_name, &inputs))
+                            {
+                                let (total, counted) = spent_sol.entry(run_id).or_default();
+                                if counted.insert(node_id) {
+                                    *total += sol;
+                                }
+                                if run.spending_cap.map_or(false, |cap| *total > cap) {
+                                    println!("run {} is over its spending cap", run_id);
+                                    let (res, _) = oneshot::channel();
+                                    finish_tx
+                                        .send(Packet {
+                                            cmd: Cmd::CancelRun(run_id),
+                                            res,
+                                        })
+                                        .ok();
+                                }
+                            }
 
-                    for edge in flow_node.outbound_edges {
-                        let props = db.read_edge_properties(edge).await.unwrap();
+                            let node_id = match run.original_node_id(node_id) {
+                                Some(node_id) => node_id,
+                                None => continue,
+                            };
+                            let flow_id = run.flow_id;
+                            // an older run still going doesn't overwrite what the latest one shows
+                            let is_latest = is_latest_run(&current_runs, &run);
+                            let network = run.network;
 
-                        if let Some(other_run_id) = props.get(RUN_ID_MARKER) {
-                            {
-                                let current_run_id = current_run_id.lock().unwrap();
-                                if &current_run_id.to_string() != other_run_id.as_str().unwrap() {
-                                    continue;
+                            if let RunState::Success(_) = state {
+                                if is_latest && !node_outputs.is_empty() {
+                                    outputs_mod.insert(node_id, node_outputs.clone());
                                 }
+                                record_transaction(
+                                    &*db,
+                                    &audit_log,
+                                    flow_id,
+                                    node_id,
+                                    network,
+                                    &inputs,
+                                    &node_outputs,
+                                    succeeded_at.unwrap_or_default(),
+                                )
+                                .await;
+                            }
+                            if let RunState::Failed(_, _) = state {
+                                set_run_state(
+                                    &current_runs,
+                                    &run_finished,
+                                    run_id,
+                                    RunStateView::Failed,
+                                );
                             }
 
-                            let log_graph = db.read_graph(edge.to).await.unwrap();
+                            println!("run status: {:?}, {:?}", node_id, state);
+                            if is_latest {
+                                // keypairs passed along stay out of the app
+                                let shown_outputs = run_outputs::redact_all(&node_outputs);
+                                run_status.insert(node_id, (state, print_output, shown_outputs));
+                            }
 
-                            let log_content = serde_json::to_string(&log_graph).unwrap();
-                            let timestamp = props
-                                .get("timestamp")
-                                .map(|v| v.as_str().unwrap().to_owned())
-                                .unwrap();
+                            let id = *req_id.lock().unwrap();
+                            rid::post(Confirm::RequestRefresh(id));
+                        }
+                        RunEvent::Completed {
+                            run_id,
+                            log_graph,
+                            timestamp,
+                        } => {
+                            spent_sol.remove(&run_id);
+                            let run = match live_run(&current_runs, run_id) {
+                                Some(run) => run,
+                                None => continue,
+                            };
 
-                            std::fs::write(
-                                format!(
-                                    "{log_path}/run_logs/{} - {}.log.json",
-                                    graph_name, timestamp
-                                ),
-                                log_content.as_bytes(),
-                            )
-                            .unwrap();
-
-                            for node in log_graph.nodes {
-                                let node_id = node
-                                    .properties
-                                    .get("original_node_id")
-                                    .unwrap()
-                                    .as_str()
-                                    .unwrap();
-                                let node_id = uuid::Uuid::from_str(node_id).unwrap();
-
-                                let entry: RunState = serde_json::from_value(
-                                    node.properties.get("state").unwrap().clone(),
-                                )
-                                .unwrap();
-
-                                let print_output = node
-                                    .properties
-                                    .get("__print_output")
-                                    .map(|e| e.as_str().unwrap().to_owned());
-
-                                let entry = (entry, print_output);
-
-                                if let Some(before) =
-                                    run_status.insert(NodeId(node_id), entry.clone())
-                                {
-                                    if before != entry {
-                                        changed = true;
-                                        println!(
-                                            "run status: {:?}, {:?}",
-                                            node.properties.get("kind").unwrap(),
-                                            entry
-                                        );
-                                    }
-                                } else {
-                                    changed = true;
-                                }
+                            // before the run is seen finished and its log read
+                            run_copy::scrub_log(&*db, run.deployed_id, log_graph).await;
+
+                            // nodes left waiting for the outputs of a failed node never run
+                            if run.state == RunStateView::Failed {
+                                cancel_log_nodes(&*db, log_graph).await;
+                            }
+
+                            write_run_log(&*db, &log_path, &run, log_graph, &timestamp).await;
+
+                            if run.state == RunStateView::Running {
+                                set_run_state(
+                                    &current_runs,
+                                    &run_finished,
+                                    run_id,
+                                    RunStateView::Success,
+                                );
                             }
+
+                            let (res, _) = oneshot::channel();
+                            finish_tx
+                                .send(Packet {
+                                    cmd: Cmd::Finish(run_id),
+                                    res,
+                                })
+                                .ok();
+
+                            let id = *req_id.lock().unwrap();
+                            rid::post(Confirm::RequestRefresh(id));
                         }
                     }
-                    if changed {
-                        let id = *req_id.lock().unwrap();
-                        // println!("{}", id);
-                        rid::post(Confirm::RequestRefresh(id));
-                    }
                 }
             });
 
             threaded_rt.block_on(async move {
+                let mut trigger_server: Option<oneshot::Sender<()>> = None;
+                let mut watches: HashMap<GraphId, oneshot::Sender<()>> = HashMap::new();
+                let mut deploys = 0;
+
+                // left by runs the app was closed during
+                run_copy::sweep(&*watch_db).await;
+
                 while let Some(packet) = rx.recv().await {
                     match packet.cmd {
-                        Cmd::Deploy(flow_id) => {
-                            let new_run_id = flow_ctx
-                                .deploy_flow(Schedule::Once, flow_id.0)
-                                .await
-                                .unwrap();
-
-                            if let Some(new_run_id) = new_run_id {
-                                let mut run_id = run_id_mod.lock().unwrap();
-                                *run_id = new_run_id;
+                        Cmd::Deploy(request, result) => {
+                            let deployed = deploy_copy(
+                                &*watch_db,
+                                &flow_ctx,
+                                &vault,
+                                address_book_id,
+                                &cached_outputs,
+                                &request,
+                            )
+                            .await;
+
+                            let deployed = deployed.map(|mut run| {
+                                // runs of the same graph go on side by side
+                                deploys += 1;
+                                run.started = deploys;
+                                let run_id = run.run_id;
+                                runs_mod.insert(run_id, run);
+                                prune_runs(&runs_mod);
+
+                                tokio::spawn(watch_run(
+                                    watch_db.clone(),
+                                    run_id,
+                                    runs_mod.clone(),
+                                    events_tx.clone(),
+                                ));
+                                run_id
+                            });
+
+                            result.send(deployed).ok();
+                            packet.res.send(()).ok();
+                        }
+                        Cmd::Finish(run_id) => {
+                            let run = runs_mod.get(&run_id).map(|run| run.value().clone());
+                            if let Some(run) = run {
+                                flow_ctx.undeploy_flow(run.deployed_id.0).ok();
+                                run_copy::delete(&*watch_db, run.deployed_id).await;
+                            }
+                            packet.res.send(()).ok();
+                        }
+                        Cmd::Cancel(flow_id) => {
+                            let canceled_runs: Vec<ActiveRun> = runs_mod
+                                .iter()
+                                .filter(|run| {
+                                    run.flow_id == flow_id && run.state == RunStateView::Running
+                                })
+                                .map(|run| run.value().clone())
+                                .collect();
+
+                            for canceled_run in canceled_runs {
+                                cancel_run(
+                                    &*watch_db,
+                                    &flow_ctx,
+                                    &runs_mod,
+                                    &trigger_ctx.run_finished,
+                                    &cancel_log_path,
+                                    canceled_run,
+                                )
+                                .await;
                             }
 
                             packet.res.send(()).unwrap();
                         }
-                        Cmd::Undeploy(flow_id) => {
-                            flow_ctx.undeploy_flow(flow_id.0).ok();
-                            {
-                                let mut run_id = run_id_mod.lock().unwrap();
-                                *run_id = Uuid::new_v4();
+                        Cmd::CancelRun(run_id) => {
+                            let canceled_run = runs_mod
+                                .get(&run_id)
+                                .map(|run| run.value().clone())
+                                .filter(|run| run.state == RunStateView::Running);
+
+                            if let Some(canceled_run) = canceled_run {
+                                cancel_run(
+                                    &*watch_db,
+                                    &flow_ctx,
+                                    &runs_mod,
+                                    &trigger_ctx.run_finished,
+                                    &cancel_log_path,
+                                    canceled_run,
+                                )
+                                .await;
                             }
+
+                            packet.res.send(()).ok();
+                        }
+                        Cmd::SetHttpTrigger(config) => {
+                            if let Some(shutdown) = trigger_server.take() {
+                                shutdown.send(()).ok();
+                            }
+
+                            if config.enabled {
+                                let (shutdown, shutdown_rx) = oneshot::channel();
+                                let port = config.port;
+                                let ctx = TriggerContext {
+                                    config,
+                                    ..trigger_ctx.clone()
+                                };
+                                tokio::spawn(http_trigger::serve(port, ctx, shutdown_rx));
+                                trigger_server = Some(shutdown);
+                            }
+
+                            packet.res.send(()).unwrap();
+                        }
+                        Cmd::SetWatchTrigger(flow_id, config) => {
+                            if let Some(shutdown) = watches.remove(&flow_id) {
+                                shutdown.send(()).ok();
+                            }
+
+                            if let Some(config) = config {
+                                let (shutdown, shutdown_rx) = oneshot::channel();
+                                tokio::spawn(watch_trigger::watch(
+                                    flow_id,
+                                    config,
+                                    trigger_ctx.clone(),
+                                    shutdown_rx,
+                                ));
+                                watches.insert(flow_id, shutdown);
+                            }
+
                             packet.res.send(()).unwrap();
                         }
                         Cmd::Stop => {
@@ -204,15 +476,58 @@ impl FlowContext {
             });
         });
 
-        FlowContext { tx, run_id }
+        FlowContext {
+            tx,
+            runs,
+            outputs,
+            run_finished: waited_runs,
+        }
+    }
+
+    /// run id of the latest deploy of a graph
+    pub fn run_id(&self, flow_id: GraphId) -> Option<Uuid> {
+        self.run(flow_id).map(|run| run.run_id)
+    }
+
+    /// latest run of a graph
+    pub fn run(&self, flow_id: GraphId) -> Option<ActiveRun> {
+        self.runs
+            .iter()
+            .filter(|run| run.flow_id == flow_id)
+            .max_by_key(|run| run.started)
+            .map(|run| run.value().clone())
+    }
+
+    pub fn run_by_id(&self, run_id: Uuid) -> Option<ActiveRun> {
+        self.runs.get(&run_id).map(|run| run.value().clone())
     }
 
-    pub async fn deploy(&self, flow_id: GraphId) {
+    /// the run once it's over, None when it isn't known
+    pub async fn wait(&self, run_id: Uuid) -> Option<ActiveRun> {
+        wait_for_run(&self.runs, &self.run_finished, run_id).await
+    }
+
+    /// starts, restarts or stops the local http server
+    pub async fn set_http_trigger(&self, config: HttpTriggerConfig) {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx
+            .send(Packet {
+                cmd: Cmd::SetHttpTrigger(config),
+                res: tx,
+            })
+            .unwrap();
+
+        rx.await.unwrap();
+    }
+
+    /// starts, restarts or stops watching the directory of a graph
+    pub async fn set_watch_trigger(&self, flow_id: GraphId, config: Option<WatchTriggerConfig>) {
         let (tx, rx) = oneshot::channel();
 
         self.tx
             .send(Packet {
-                cmd: Cmd::Deploy(flow_id),
+                cmd: Cmd::SetWatchTrigger(flow_id, config),
                 res: tx,
             })
             .unwrap();
@@ -220,12 +535,37 @@ impl FlowContext {
         rx.await.unwrap();
     }
 
-    pub async fn undeploy(&self, flow_id: GraphId) {
+    /// runs still going and the latest finished ones, of every graph
+    pub fn active_runs(&self) -> Vec<ActiveRun> {
+        self.runs.iter().map(|run| run.value().clone()).collect()
+    }
+
+    /// values given to the outputs of the node by its latest successful run
+    pub fn cached_outputs(&self, node_id: NodeId) -> Option<Properties> {
+        self.outputs
+            .get(&node_id)
+            .map(|outputs| outputs.value().clone())
+    }
+
+    /// the graph has a run still going
+    pub fn is_deployed(&self, flow_id: GraphId) -> bool {
+        self.runs
+            .iter()
+            .any(|run| run.flow_id == flow_id && run.state == RunStateView::Running)
+    }
+
+    /// runs a copy of the graph made from the db, returns the run id
+    pub async fn deploy(&self, request: RunRequest) -> Result<Uuid, DeployError> {
+        RunSender(self.tx.clone()).deploy(request).await
+    }
+
+    /// stop the runs of the graph, unfinished nodes are marked canceled in the run logs
+    pub async fn cancel(&self, flow_id: GraphId) {
         let (tx, rx) = oneshot::channel();
 
         self.tx
             .send(Packet {
-                cmd: Cmd::Undeploy(flow_id),
+                cmd: Cmd::Cancel(flow_id),
                 res: tx,
             })
             .unwrap();
@@ -234,6 +574,70 @@ impl FlowContext {
     }
 }
 
+// run still receiving events
+fn live_run(runs: &DashMap<Uuid, ActiveRun>, run_id: Uuid) -> Option<ActiveRun> {
+    runs.get(&run_id)
+        .map(|run| run.value().clone())
+        .filter(|run| run.state != RunStateView::Canceled)
+}
+
+/// the run once it succeeded, failed or was canceled,
+/// other runs of the same graph finishing first don't end the wait
+pub async fn wait_for_run(
+    runs: &DashMap<Uuid, ActiveRun>,
+    run_finished: &Notify,
+    run_id: Uuid,
+) -> Option<ActiveRun> {
+    loop {
+        // created first, a run finishing in between isn't missed
+        let notified = run_finished.notified();
+
+        match runs.get(&run_id).map(|run| run.value().clone()) {
+            Some(run) if run.state == RunStateView::Running => {}
+            Some(run) => return Some(run),
+            None => return None, // dropped with the oldest finished runs
+        }
+
+        notified.await;
+    }
+}
+
+fn set_run_state(
+    runs: &DashMap<Uuid, ActiveRun>,
+    run_finished: &Notify,
+    run_id: Uuid,
+    state: RunStateView,
+) {
+    if let Some(mut run) = runs.get_mut(&run_id) {
+        run.state = state;
+    }
+    run_finished.notify_waiters();
+}
+
+// no later deploy of the same graph
+fn is_latest_run(runs: &DashMap<Uuid, ActiveRun>, run: &ActiveRun) -> bool {
+    !runs
+        .iter()
+        .any(|other| other.flow_id == run.flow_id && other.started > run.started)
+}
+
+// drops the oldest finished runs past MAX_FINISHED_RUNS
+fn prune_runs(runs: &DashMap<Uuid, ActiveRun>) {
+    let mut finished: Vec<(u64, Uuid)> = runs
+        .iter()
+        .filter(|run| run.state != RunStateView::Running)
+        .map(|run| (run.started, run.run_id))
+        .collect();
+    if finished.len() <= MAX_FINISHED_RUNS {
+        return;
+    }
+
+    finished.sort();
+    for (_, run_id) in finished.iter().take(finished.len() - MAX_FINISHED_RUNS) {
+        runs.remove(run_id);
+    }
+}
+
 impl Drop for FlowContext {
     fn drop(&mut self) {
         let (tx, rx) = oneshot::channel();
@@ -248,3 +652,895 @@ impl Drop for FlowContext {
         block_on(rx).unwrap();
     }
 }
+
+// copy of the graph with the values of the request, deployed on the runner,
+// the run is started by the command loop
+async fn deploy_copy(
+    db: &dyn Datastore,
+    flow_ctx: &InnerFlowContext,
+    vault: &Mutex<Vault>,
+    address_book_id: GraphId,
+    outputs: &DashMap<NodeId, Properties>,
+    request: &RunRequest,
+) -> Result<ActiveRun, DeployError> {
+    let flow = FlowGraph::read(db, request.flow_id)
+        .await
+        .filter(FlowGraph::is_flow)
+        .ok_or_else(|| DeployError::Runner("graph not found".to_owned()))?;
+    let solana_net = flow.solana_net();
+
+    // the trigger node isn't run, its outputs are the file of the request
+    let trigger_node_ids: HashSet<NodeId> = flow
+        .nodes
+        .keys()
+        .filter(|node_id| flow.command_name(**node_id) == Some(WATCH_TRIGGER_COMMAND))
+        .copied()
+        .collect();
+
+    let input_texts = check_deploy(&flow, &request.input_values, &vault.lock().unwrap())?;
+
+    // nobody reviews what an unattended run signs
+    if request.unattended && solana_net.is_mainnet() && flow.spending_cap().is_none() {
+        return Err(DeployError::SpendingCap(
+            "mainnet flows need a spending cap to be run unattended".to_owned(),
+        ));
+    }
+
+    let node_ids = match &request.plan {
+        Some(plan) => &plan.node_ids - &trigger_node_ids,
+        None => &flow.node_ids() - &trigger_node_ids,
+    };
+    let plan = partial_run::plan_nodes(node_ids, &flow.edges);
+
+    // inputs connected to nodes not run again get the outputs of their latest run
+    let mut consts = Vec::new();
+    let mut missing_node_ids = Vec::new();
+    for edge in plan.cached.iter() {
+        if trigger_node_ids.contains(&edge.from) {
+            let value = request
+                .trigger_outputs
+                .get(&edge.output)
+                .and_then(|value| serde_json::from_value(value.clone()).ok())
+                .ok_or_else(|| {
+                    DeployError::Runner(format!("the watch trigger gave no {}", edge.output))
+                })?;
+            consts.push((edge.to, edge.input.clone(), value));
+            continue;
+        }
+
+        let value = outputs
+            .get(&edge.from)
+            .and_then(|outputs| serde_json::from_value(outputs.get(&edge.output)?.clone()).ok());
+
+        match value {
+            Some(value) => consts.push((edge.to, edge.input.clone(), value)),
+            None if !missing_node_ids.contains(&edge.from) => missing_node_ids.push(edge.from),
+            None => {}
+        }
+    }
+    if !missing_node_ids.is_empty() {
+        return Err(DeployError::MissingCachedOutput(missing_node_ids));
+    }
+
+    // an edge overrides the literal
+    for node_id in plan.node_ids.iter() {
+        for (input, literal) in flow.literals(*node_id) {
+            if plan.is_connected(*node_id, &input) {
+                continue;
+            }
+            let value = serde_json::from_str(&literal)
+                .map_err(|e| DeployError::Runner(format!("invalid value of {}: {}", input, e)))?;
+            consts.push((*node_id, input, value));
+        }
+    }
+
+    let address_book = match db
+        .execute(Action::Query(QueryKind::ReadNode(address_book_id.0)))
+        .await
+    {
+        Ok(node) => AddressBook::from_properties(&node.into_node().unwrap().properties),
+        Err(_) => AddressBook::default(),
+    };
+    let (configs, secret_node_ids) = run_copy::resolve_configs(
+        &flow,
+        &plan.node_ids,
+        &input_texts,
+        solana_net.name(),
+        &vault.lock().unwrap(),
+        &address_book,
+    )?;
+
+    let taps = run_outputs::tap_outputs(&flow, &plan);
+    let copy = run_copy::create(db, &flow, &plan, consts, &taps, &configs, &secret_node_ids).await;
+
+    let run_id = match flow_ctx.deploy_flow(Schedule::Once, copy.graph_id.0).await {
+        Ok(Some(run_id)) => run_id,
+        Ok(None) => {
+            run_copy::delete(db, copy.graph_id).await;
+            return Err(DeployError::Runner("flow didn't start".to_owned()));
+        }
+        Err(e) => {
+            run_copy::delete(db, copy.graph_id).await;
+            return Err(DeployError::Runner(format!("{:?}", e)));
+        }
+    };
+
+    // the runner read the copy while deploying
+    run_copy::restore(db, &flow, &copy, &configs).await;
+
+    let command_names = copy
+        .node_ids
+        .iter()
+        .filter_map(|(copy_id, node_id)| Some((*copy_id, flow.command_name(*node_id)?.to_owned())))
+        .collect();
+
+    Ok(ActiveRun {
+        run_id,
+        flow_id: request.flow_id,
+        started: 0,
+        network: solana_net.name().to_owned(),
+        state: RunStateView::Running,
+        inputs: recorded_inputs(&flow, &input_texts),
+        deployed_id: copy.graph_id,
+        node_ids: copy.node_ids,
+        edges: copy.edges,
+        session: request.session,
+        command_names,
+        spending_cap: flow.spending_cap(),
+    })
+}
+
+// stops the run and writes its log, its events are dropped from now on
+async fn cancel_run(
+    db: &dyn Datastore,
+    flow_ctx: &InnerFlowContext,
+    runs: &DashMap<Uuid, ActiveRun>,
+    run_finished: &Notify,
+    log_path: &str,
+    run: ActiveRun,
+) {
+    set_run_state(runs, run_finished, run.run_id, RunStateView::Canceled);
+
+    // the runner stops scheduling nodes, transactions already sent can't be aborted
+    flow_ctx.undeploy_flow(run.deployed_id.0).ok();
+
+    if let Some((log_graph, timestamp)) = find_log_graph(db, run.deployed_id, run.run_id).await {
+        run_copy::scrub_log(db, run.deployed_id, log_graph).await;
+        cancel_log_nodes(db, log_graph).await;
+        write_run_log(db, log_path, &run, log_graph, &timestamp).await;
+    }
+    run_copy::delete(db, run.deployed_id).await;
+}
+
+// the runner writes the state of each node in the log graph of the run,
+// changes are forwarded as events until every node is done
+async fn watch_run(
+    db: Arc<dyn Datastore>,
+    run_id: Uuid,
+    runs: Arc<DashMap<Uuid, ActiveRun>>,
+    events: mpsc::UnboundedSender<RunEvent>,
+) {
+    let mut interval = tokio::time::interval(RUN_WATCH_INTERVAL);
+    let mut log_graph = None;
+    let mut states: HashMap<Uuid, (RunState, Option<String>, Properties)> = HashMap::new();
+    // the runner only logs how long a node took, its outputs can be read a few polls later
+    let mut succeeded_at: HashMap<Uuid, String> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        let run = match live_run(&runs, run_id) {
+            Some(run) => run,
+            None => return,
+        };
+        let deployed_id = run.deployed_id;
+
+        // linked to the deployed graph node once the run started
+        let (log_graph_id, timestamp) = match log_graph.clone() {
+            Some(log_graph) => log_graph,
+            None => match find_log_graph(&*db, deployed_id, run_id).await {
+                Some(found) => {
+                    log_graph = Some(found.clone());
+                    found
+                }
+                None => continue,
+            },
+        };
+
+        let graph = match db.read_graph(log_graph_id).await {
+            Ok(graph) => graph,
+            Err(_) => continue,
+        };
+
+        let mut finished = !graph.nodes.is_empty();
+        let mut node_states = HashMap::new();
+
+        // not written completely by the runner yet when there's no node id
+        let log_node_id = |properties: &Properties| {
+            properties
+                .get("original_node_id")
+                .and_then(|node_id| node_id.as_str())
+                .and_then(|node_id| Uuid::from_str(node_id).ok())
+        };
+        let logged_inputs: HashMap<NodeId, Properties> = graph
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let inputs = node.properties.get("inputs")?.as_object()?.clone();
+                Some((NodeId(log_node_id(&node.properties)?), inputs))
+            })
+            .collect();
+        let mut outputs = run_outputs::captured_outputs(&run.edges, &logged_inputs);
+
+        for node in graph.nodes {
+            let node_id = match log_node_id(&node.properties) {
+                Some(node_id) => node_id,
+                None => continue,
+            };
+            let state = match node
+                .properties
+                .get("state")
+                .and_then(|state| serde_json::from_value::<RunState>(state.clone()).ok())
+            {
+                Some(state) => state,
+                None => continue,
+            };
+
+            let print_output = node
+                .properties
+                .get("__print_output")
+                .and_then(|print_output| print_output.as_str())
+                .map(str::to_owned);
+
+            finished &= matches!(
+                state,
+                RunState::Success(_) | RunState::Failed(_, _) | RunState::Canceled
+            );
+            node_states.insert(NodeId(node_id), state.clone());
+
+            if let RunState::Success(_) = state {
+                succeeded_at
+                    .entry(node_id)
+                    .or_insert_with(|| chrono::Utc::now().to_rfc3339());
+            }
+
+            // outputs are read once the nodes they feed ran
+            let node_outputs = outputs.remove(&NodeId(node_id)).unwrap_or_default();
+            let entry = (state.clone(), print_output.clone(), node_outputs.clone());
+            if states.get(&node_id) == Some(&entry) {
+                continue;
+            }
+            states.insert(node_id, entry);
+
+            let inputs = logged_inputs
+                .get(&NodeId(node_id))
+                .cloned()
+                .unwrap_or_default();
+            let event = RunEvent::NodeState {
+                run_id,
+                node_id: NodeId(node_id),
+                state,
+                print_output,
+                outputs: node_outputs,
+                inputs,
+                succeeded_at: succeeded_at.get(&node_id).cloned(),
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+
+        if finished || is_run_over(&run, &node_states) {
+            events
+                .send(RunEvent::Completed {
+                    run_id,
+                    log_graph: log_graph_id,
+                    timestamp,
+                })
+                .ok();
+            return;
+        }
+    }
+}
+
+// the runner doesn't start nodes downstream of a failed one, so a run is over
+// once each node of the copy is done or waits on a node that failed
+fn is_run_over(run: &ActiveRun, states: &HashMap<NodeId, RunState>) -> bool {
+    let is_done = |node_id: &NodeId| {
+        matches!(
+            states.get(node_id),
+            Some(RunState::Success(_) | RunState::Failed(_, _) | RunState::Canceled)
+        )
+    };
+
+    let mut blocked: HashSet<NodeId> = states
+        .iter()
+        .filter(|(_, state)| matches!(state, RunState::Failed(_, _) | RunState::Canceled))
+        .map(|(node_id, _)| *node_id)
+        .collect();
+    let mut queue: Vec<NodeId> = blocked.iter().copied().collect();
+    while let Some(node_id) = queue.pop() {
+        for edge in run.edges.iter().filter(|edge| edge.from == node_id) {
+            if !is_done(&edge.to) && blocked.insert(edge.to) {
+                queue.push(edge.to);
+            }
+        }
+    }
+
+    run.node_ids
+        .keys()
+        .chain(run.edges.iter().flat_map(|edge| [&edge.from, &edge.to]))
+        .all(|node_id| is_done(node_id) || blocked.contains(node_id))
+}
+
+/// log graph and timestamp of a run, deployed_id is the copy the runner ran
+pub async fn find_log_graph(
+    db: &dyn Datastore,
+    deployed_id: GraphId,
+    run_id: Uuid,
+) -> Option<(Uuid, String)> {
+    let flow_node = db
+        .execute(Action::Query(QueryKind::ReadNode(deployed_id.0)))
+        .await
+        .unwrap()
+        .into_node()
+        .unwrap();
+
+    for edge in flow_node.outbound_edges {
+        let props = db.read_edge_properties(edge).await.unwrap();
+
+        if props.get(RUN_ID_MARKER).and_then(|id| id.as_str()) == Some(&run_id.to_string()) {
+            let timestamp = props
+                .get("timestamp")
+                .map(|v| v.as_str().unwrap().to_owned())
+                .unwrap();
+            return Some((edge.to, timestamp));
+        }
+    }
+
+    None
+}
+
+// nodes of the log graph still waiting or running are recorded as canceled
+async fn cancel_log_nodes(db: &dyn Datastore, log_graph: Uuid) {
+    let graph = match db.read_graph(log_graph).await {
+        Ok(graph) => graph,
+        Err(_) => return,
+    };
+    let canceled_at = chrono::Utc::now().to_rfc3339();
+
+    for node in graph.nodes {
+        // nodes the runner hasn't written a state for yet are left alone
+        let state = node
+            .properties
+            .get("state")
+            .and_then(|state| serde_json::from_value::<RunState>(state.clone()).ok());
+        if !matches!(state, Some(RunState::WaitingInputs | RunState::Running)) {
+            continue;
+        }
+
+        let mut props = node.properties;
+        props.insert(
+            "state".into(),
+            serde_json::to_value(RunState::Canceled).unwrap(),
+        );
+        props.insert("canceled_at".into(), canceled_at.clone().into());
+
+        db.execute(Action::Mutate(
+            log_graph,
+            MutateKind::UpdateNode((node.node_id, props)),
+        ))
+        .await
+        .ok();
+    }
+}
+
+async fn write_run_log(
+    db: &dyn Datastore,
+    log_path: &str,
+    run: &ActiveRun,
+    log_graph: Uuid,
+    timestamp: &str,
+) {
+    let graph_name = graph_name(db, run.flow_id).await;
+    let log_graph = db.read_graph(log_graph).await.unwrap();
+
+    // values the flow inputs resolved to, next to the graph
+    let mut log_content = serde_json::to_value(&log_graph).unwrap();
+    if let Some(log_content) = log_content.as_object_mut() {
+        log_content.insert("flow_inputs".into(), run.inputs.clone().into());
+    }
+
+    // runs started at once, e.g. by files changed together, get a file each,
+    // the segments of a debug session add their nodes to the file of the first one
+    let run_logs = Path::new(log_path).join("run_logs");
+    let log_name = format!(" - {}.log.json", run.session.unwrap_or(run.run_id));
+    let session_log = std::fs::read_dir(&run_logs)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().ends_with(&log_name));
+
+    let path = match session_log {
+        Some(path) => {
+            let session_content = std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok());
+            if let Some(mut session_content) = session_content {
+                let nodes = log_content
+                    .get("nodes")
+                    .and_then(|nodes| nodes.as_array())
+                    .cloned()
+                    .unwrap_or_default();
+                if let Some(session_nodes) = session_content
+                    .get_mut("nodes")
+                    .and_then(|nodes| nodes.as_array_mut())
+                {
+                    session_nodes.extend(nodes);
+                }
+                log_content = session_content;
+            }
+            path
+        }
+        None => run_logs.join(format!("{} - {}{}", graph_name, timestamp, log_name)),
+    };
+
+    std::fs::write(
+        path,
+        serde_json::to_string(&log_content).unwrap().as_bytes(),
+    )
+    .unwrap();
+}
+
+async fn graph_name(db: &dyn Datastore, graph_id: GraphId) -> String {
+    db.execute(Action::Query(QueryKind::ReadNode(graph_id.0)))
+        .await
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties
+        .get("name")
+        .unwrap()
+        .as_str()
+        .unwrap()
+        .to_owned()
+}
+
+// on-chain transaction of a successful node, recorded once its signature output is read
+#[allow(clippy::too_many_arguments)]
+async fn record_transaction(
+    db: &dyn Datastore,
+    audit_log: &Mutex<AuditLog>,
+    graph_id: GraphId,
+    node_id: NodeId,
+    network: String,
+    inputs: &Properties,
+    outputs: &Properties,
+    succeeded_at: String,
+) {
+    let command_name = match db
+        .execute(Action::Query(QueryKind::ReadNode(node_id.0)))
+        .await
+    {
+        Ok(original) => original
+            .into_node()
+            .unwrap()
+            .properties
+            .get(COMMAND_NAME_MARKER)
+            .and_then(|name| name.as_str())
+            .unwrap_or_default()
+            .to_owned(),
+        Err(_) => return, // deleted since the run
+    };
+
+    let transaction = transaction_outputs(&command_name, inputs, outputs)
+        .filter(|(signature, _, _)| !audit_log.lock().unwrap().contains(signature));
+    let (signature, signers, amounts) = match transaction {
+        Some(transaction) => transaction,
+        None => return,
+    };
+
+    let audit_entry = AuditEntry {
+        timestamp: succeeded_at,
+        graph_id: graph_id.0.to_string(),
+        graph_name: graph_name(db, graph_id).await,
+        node_id: node_id.0.to_string(),
+        command_name,
+        network,
+        signature,
+        signers,
+        amounts,
+    };
+    if let Err(e) = audit_log.lock().unwrap().record(audit_entry) {
+        eprintln!("error while writing audit log: {}", e);
+    }
+}
+
+#[test]
+fn flow_context_runs_by_id() {
+    let flow_id = GraphId(Uuid::new_v4());
+    let run = |started, state| ActiveRun {
+        run_id: Uuid::new_v4(),
+        flow_id,
+        started,
+        network: "devnet".to_owned(),
+        state,
+        inputs: Properties::new(),
+        deployed_id: GraphId(Uuid::new_v4()),
+        node_ids: HashMap::new(),
+        edges: Vec::new(),
+        session: None,
+        command_names: HashMap::new(),
+        spending_cap: None,
+    };
+
+    // a redeploy keeps the previous run
+    let runs = DashMap::new();
+    let first = run(1, RunStateView::Running);
+    let second = run(2, RunStateView::Running);
+    runs.insert(first.run_id, first.clone());
+    runs.insert(second.run_id, second.clone());
+    assert!(!is_latest_run(&runs, &first));
+    assert!(is_latest_run(&runs, &second));
+
+    set_run_state(&runs, &Notify::new(), first.run_id, RunStateView::Canceled);
+    assert_eq!(live_run(&runs, first.run_id), None);
+    assert_eq!(live_run(&runs, second.run_id), Some(second.clone()));
+
+    // only the oldest finished runs are dropped
+    for started in 3..MAX_FINISHED_RUNS as u64 + 3 {
+        let finished = run(started, RunStateView::Success);
+        runs.insert(finished.run_id, finished);
+    }
+    prune_runs(&runs);
+    assert_eq!(runs.len(), MAX_FINISHED_RUNS + 1);
+    assert!(!runs.contains_key(&first.run_id));
+    assert!(runs.contains_key(&second.run_id));
+}
+
+// flow context on a new db with a "const" node printed by a "print" node
+#[cfg(test)]
+fn test_flow_context() -> (
+    FlowContext,
+    Arc<DashMap<NodeId, (RunState, Option<String>, Properties)>>,
+    GraphId,
+    NodeId,
+    NodeId,
+    std::path::PathBuf,
+) {
+    use sunshine_solana::commands::simple::Command as SimpleCommand;
+
+    let const_config = SimpleCommand::Const(sunshine_solana::Value::String("hello".to_owned()));
+    test_flow("const", const_config, "output")
+}
+
+// stops the flow context before its db and run logs are removed
+#[cfg(test)]
+fn test_cleanup(flow_context: FlowContext, dir: std::path::PathBuf) {
+    drop(flow_context);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// flow of a source node whose output is printed, and the directory of its db and run logs
+#[cfg(test)]
+fn test_flow(
+    source: &str,
+    source_config: sunshine_solana::commands::simple::Command,
+    output: &str,
+) -> (
+    FlowContext,
+    Arc<DashMap<NodeId, (RunState, Option<String>, Properties)>>,
+    GraphId,
+    NodeId,
+    NodeId,
+    std::path::PathBuf,
+) {
+    use crate::model::FLOW_GRAPH_MARKER;
+    use sunshine_core::msg::CreateEdge;
+    use sunshine_indra::store::{DbConfig, DB};
+    use sunshine_solana::commands::simple::Command as SimpleCommand;
+    use sunshine_solana::{
+        CommandConfig, COMMAND_MARKER, INPUT_ARG_NAME_MARKER, OUTPUT_ARG_NAME_MARKER,
+    };
+
+    let dir = std::env::temp_dir().join(format!("flow_context_{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db: Arc<dyn Datastore> = Arc::new(
+        DB::new(&DbConfig {
+            db_path: dir.join("db").to_str().unwrap().to_owned(),
+        })
+        .unwrap(),
+    );
+
+    let props = serde_json::json!({ "name": "capture", FLOW_GRAPH_MARKER: true });
+    let graph_id = block_on(db.execute(Action::CreateGraph(props.as_object().unwrap().clone())))
+        .unwrap()
+        .as_id()
+        .unwrap();
+    let create_node = |name: &str, config: SimpleCommand| {
+        let mut props = Properties::new();
+        props.insert(COMMAND_NAME_MARKER.into(), name.into());
+        props.insert(
+            COMMAND_MARKER.into(),
+            serde_json::to_value(CommandConfig::Simple(config)).unwrap(),
+        );
+        let node_id = block_on(db.execute(Action::Mutate(graph_id, MutateKind::CreateNode(props))))
+            .unwrap()
+            .as_id()
+            .unwrap();
+        NodeId(node_id)
+    };
+    let source_id = create_node(source, source_config);
+    let print_id = create_node("print", SimpleCommand::Print);
+
+    let mut properties = Properties::new();
+    properties.insert(INPUT_ARG_NAME_MARKER.into(), "print".into());
+    properties.insert(OUTPUT_ARG_NAME_MARKER.into(), output.into());
+    block_on(db.execute(Action::Mutate(
+        graph_id,
+        MutateKind::CreateEdge(CreateEdge {
+            from: source_id.0,
+            to: print_id.0,
+            properties,
+        }),
+    )))
+    .unwrap();
+
+    let run_status = Arc::new(DashMap::new());
+    let flow_context = FlowContext::new(
+        db,
+        run_status.clone(),
+        Arc::new(Mutex::new(0)),
+        GraphEntry {
+            id: graph_id.to_string(),
+            name: "capture".to_owned(),
+        },
+        dir.to_str().unwrap().to_owned(),
+        Arc::new(Mutex::new(AuditLog::open(dir.join("audit.jsonl")))),
+        Arc::new(Mutex::new(Vault::open(dir.join("vault.json")).unwrap())),
+        GraphId(Uuid::new_v4()), // no address book
+    );
+
+    (
+        flow_context,
+        run_status,
+        GraphId(graph_id),
+        source_id,
+        print_id,
+        dir,
+    )
+}
+
+// state of the run once it's over
+#[cfg(test)]
+fn test_wait(flow_context: &FlowContext, run_id: Uuid) -> RunStateView {
+    let wait = async {
+        tokio::time::timeout(
+            tokio::time::Duration::from_secs(30),
+            flow_context.wait(run_id),
+        )
+        .await
+    };
+    match tokio::runtime::Runtime::new().unwrap().block_on(wait) {
+        Ok(Some(run)) => run.state,
+        Ok(None) => panic!("run not found"),
+        Err(_) => panic!("run didn't finish"),
+    }
+}
+
+#[cfg(test)]
+fn test_request(flow_id: GraphId, plan: Option<PartialRunPlan>) -> RunRequest {
+    RunRequest {
+        flow_id,
+        input_values: Properties::new(),
+        trigger_outputs: Properties::new(),
+        unattended: false,
+        session: None,
+        plan,
+    }
+}
+
+#[test]
+fn flow_context_runs_plan_with_cached_outputs() {
+    let (flow_context, run_status, flow_id, const_id, print_id, dir) = test_flow_context();
+    let edges = vec![PortEdge {
+        from: const_id,
+        output: "output".to_owned(),
+        to: print_id,
+        input: "print".to_owned(),
+    }];
+    let plan = partial_run::plan_nodes([print_id].into_iter().collect(), &edges);
+
+    // the const never ran
+    let result = block_on(flow_context.deploy(test_request(flow_id, Some(plan.clone()))));
+    assert!(matches!(
+        result,
+        Err(DeployError::MissingCachedOutput(node_ids)) if node_ids == vec![const_id]
+    ));
+
+    let run_id = block_on(flow_context.deploy(test_request(flow_id, None))).unwrap();
+    assert_eq!(test_wait(&flow_context, run_id), RunStateView::Success);
+
+    // the value the print node received is the output of the const
+    let hello = serde_json::json!({ "String": "hello" });
+    let (state, _, outputs) = run_status.get(&const_id).unwrap().value().clone();
+    assert!(matches!(state, RunState::Success(_)));
+    assert_eq!(outputs.get("output"), Some(&hello));
+    assert_eq!(
+        flow_context.cached_outputs(const_id).unwrap().get("output"),
+        Some(&hello)
+    );
+    run_status.clear();
+
+    // only the print node runs again, with the value the const gave
+    let run_id = block_on(flow_context.deploy(test_request(flow_id, Some(plan)))).unwrap();
+    assert_eq!(test_wait(&flow_context, run_id), RunStateView::Success);
+    assert!(run_status.get(&const_id).is_none());
+    let (state, print_output, _) = run_status.get(&print_id).unwrap().value().clone();
+    assert!(matches!(state, RunState::Success(_)));
+    assert!(print_output.unwrap().contains("hello"));
+
+    test_cleanup(flow_context, dir);
+}
+
+#[test]
+fn flow_context_runs_same_flow_concurrently() {
+    let (flow_context, _, flow_id, _, print_id, dir) = test_flow_context();
+
+    // two requests on the same flow at once, e.g. two run_sync calls
+    let (first, second) = block_on(futures::future::join(
+        flow_context.deploy(test_request(flow_id, None)),
+        flow_context.deploy(test_request(flow_id, None)),
+    ));
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_ne!(first, second);
+
+    // each waits for its own run, neither is canceled by the other
+    assert_eq!(test_wait(&flow_context, second), RunStateView::Success);
+    assert_eq!(test_wait(&flow_context, first), RunStateView::Success);
+
+    for run_id in [first, second] {
+        let run = flow_context.run_by_id(run_id).unwrap();
+        assert!(run.node_ids.values().any(|node_id| *node_id == print_id));
+    }
+
+    test_cleanup(flow_context, dir);
+}
+
+#[test]
+fn flow_context_runs_per_watched_file() {
+    use sunshine_solana::commands::simple::Command as SimpleCommand;
+
+    let trigger_config = SimpleCommand::Const(sunshine_solana::Value::String("".to_owned()));
+    let (flow_context, _, flow_id, _, _, dir) =
+        test_flow(WATCH_TRIGGER_COMMAND, trigger_config, "path");
+
+    // a manual run has no file to give
+    let result = block_on(flow_context.deploy(test_request(flow_id, None)));
+    assert!(matches!(result, Err(DeployError::Runner(_))));
+
+    // files changed together
+    let request = |path: &str| RunRequest {
+        trigger_outputs: serde_json::json!({ "path": { "String": path } })
+            .as_object()
+            .unwrap()
+            .clone(),
+        ..test_request(flow_id, None)
+    };
+    let (first, second) = block_on(futures::future::join(
+        flow_context.deploy(request("a.csv")),
+        flow_context.deploy(request("b.csv")),
+    ));
+    let (first, second) = (first.unwrap(), second.unwrap());
+    assert_eq!(test_wait(&flow_context, first), RunStateView::Success);
+    assert_eq!(test_wait(&flow_context, second), RunStateView::Success);
+
+    // each run printed its own file, in a log of its own
+    let logs: Vec<std::path::PathBuf> = std::fs::read_dir(dir.join("run_logs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(logs.len(), 2);
+    for (run_id, path) in [(first, "a.csv"), (second, "b.csv")] {
+        let log = logs
+            .iter()
+            .find(|log| log.to_string_lossy().contains(&run_id.to_string()))
+            .unwrap();
+        assert!(std::fs::read_to_string(log).unwrap().contains(path));
+    }
+
+    test_cleanup(flow_context, dir);
+}
+
+#[test]
+fn flow_context_debug_session_shares_log() {
+    let (flow_context, _, flow_id, const_id, print_id, dir) = test_flow_context();
+    let edges = vec![PortEdge {
+        from: const_id,
+        output: "output".to_owned(),
+        to: print_id,
+        input: "print".to_owned(),
+    }];
+    let session = Some(Uuid::new_v4());
+
+    // paused before the print, then resumed with the output of the first segment
+    let segments = [
+        partial_run::plan_nodes([const_id, print_id].into_iter().collect(), &edges),
+        partial_run::plan_nodes([print_id].into_iter().collect(), &edges),
+    ];
+    let mut runs = Vec::new();
+    for plan in segments {
+        let request = RunRequest {
+            session,
+            ..test_request(flow_id, Some(plan))
+        };
+        let run_id = block_on(flow_context.deploy(request)).unwrap();
+        assert_eq!(test_wait(&flow_context, run_id), RunStateView::Success);
+        let run = flow_context.run_by_id(run_id).unwrap();
+        assert_eq!(run.session, session);
+        runs.push(run);
+    }
+
+    let logs: Vec<std::path::PathBuf> = std::fs::read_dir(dir.join("run_logs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0]
+        .to_string_lossy()
+        .ends_with(&format!("{}.log.json", session.unwrap())));
+
+    // the print node of both segments
+    let log = std::fs::read_to_string(&logs[0]).unwrap();
+    for run in runs {
+        let (copy_id, _) = run
+            .node_ids
+            .iter()
+            .find(|(_, node_id)| **node_id == print_id)
+            .unwrap();
+        assert!(log.contains(&copy_id.0.to_string()));
+    }
+
+    test_cleanup(flow_context, dir);
+}
+
+#[test]
+fn run_is_over_once_nodes_wait_on_a_failure() {
+    let [a, b, c, d] = [(); 4].map(|_| NodeId(Uuid::new_v4()));
+    let edge = |from, to| PortEdge {
+        from,
+        output: "output".to_owned(),
+        to,
+        input: "input".to_owned(),
+    };
+    // a -> b -> c, d on its own
+    let run = ActiveRun {
+        run_id: Uuid::new_v4(),
+        flow_id: GraphId(Uuid::new_v4()),
+        started: 0,
+        network: "devnet".to_owned(),
+        state: RunStateView::Running,
+        inputs: Properties::new(),
+        deployed_id: GraphId(Uuid::new_v4()),
+        node_ids: [a, b, c, d]
+            .iter()
+            .map(|node_id| (*node_id, *node_id))
+            .collect(),
+        edges: vec![edge(a, b), edge(b, c)],
+        session: None,
+        command_names: HashMap::new(),
+        spending_cap: None,
+    };
+
+    let mut states = HashMap::new();
+    states.insert(a, RunState::Success(1));
+    states.insert(b, RunState::Failed(1, "error".to_owned()));
+    assert!(!is_run_over(&run, &states));
+
+    states.insert(d, RunState::Running);
+    assert!(!is_run_over(&run, &states));
+
+    states.insert(d, RunState::Success(1));
+    assert!(is_run_over(&run, &states));
+}
@@ -0,0 +1,218 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value as JsonValue;
+use sunshine_core::msg::{Action, QueryKind};
+use sunshine_core::store::Datastore;
+use sunshine_solana::commands::solana;
+use sunshine_solana::{
+    COMMAND_NAME_MARKER, CTX_MARKER, INPUT_ARG_NAME_MARKER, OUTPUT_ARG_NAME_MARKER,
+};
+
+use crate::flow_input::{FlowInput, FLOW_INPUT_MARKER};
+use crate::model::{
+    GraphId, NodeId, SolanaEndpointConfig, SolanaNet, ADDITIONAL_DATA_MARKER, FLOW_GRAPH_MARKER,
+    INPUT_LITERALS_MARKER, LITERAL_NODE_MARKER, NETWORK_VALUES_MARKER, SOLANA_ENDPOINT_MARKER,
+    SPENDING_CAP_MARKER, TEXT_MARKER,
+};
+use crate::partial_run::PortEdge;
+use crate::validation::const_text_payload;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// flow as stored in the db, read by the flow context to run it
+/// whichever graph is open in the app
+#[derive(Debug, Clone)]
+pub struct FlowGraph {
+    pub graph_id: GraphId,
+    pub properties: Properties, // of the graph node
+    pub ctx_node: Option<(NodeId, Properties)>,
+    pub nodes: HashMap<NodeId, Properties>, // command nodes
+    pub ctx_edges: Vec<NodeId>,             // command nodes linked to the context node
+    pub edges: Vec<PortEdge>,
+}
+
+impl FlowGraph {
+    /// None when the graph doesn't exist
+    pub async fn read(db: &dyn Datastore, graph_id: GraphId) -> Option<Self> {
+        let properties = match db
+            .execute(Action::Query(QueryKind::ReadNode(graph_id.0)))
+            .await
+        {
+            Ok(node) => node.into_node().unwrap().properties,
+            Err(_) => return None,
+        };
+        let graph = db.read_graph(graph_id.0).await.ok()?;
+
+        let mut flow = FlowGraph {
+            graph_id,
+            properties,
+            ctx_node: None,
+            nodes: HashMap::new(),
+            ctx_edges: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for node in graph.nodes {
+            let node_id = NodeId(node.node_id);
+            let is_ctx = node.properties.contains_key(CTX_MARKER);
+            // consts older versions created for literals aren't part of the flow
+            let is_command = node
+                .properties
+                .get(COMMAND_NAME_MARKER)
+                .map_or(false, JsonValue::is_string)
+                && !node.properties.contains_key(LITERAL_NODE_MARKER);
+            if !is_ctx && !is_command {
+                continue;
+            }
+
+            for edge in node.outbound_edges {
+                if is_ctx {
+                    flow.ctx_edges.push(NodeId(edge.to));
+                    continue;
+                }
+
+                let props = match db.read_edge_properties(edge).await {
+                    Ok(props) => props,
+                    Err(_) => continue,
+                };
+                let input = props.get(INPUT_ARG_NAME_MARKER).and_then(JsonValue::as_str);
+                let output = props
+                    .get(OUTPUT_ARG_NAME_MARKER)
+                    .and_then(JsonValue::as_str);
+                if let (Some(input), Some(output)) = (input, output) {
+                    flow.edges.push(PortEdge {
+                        from: node_id,
+                        output: output.to_owned(),
+                        to: NodeId(edge.to),
+                        input: input.to_owned(),
+                    });
+                }
+            }
+
+            if is_ctx {
+                flow.ctx_node = Some((node_id, node.properties));
+            } else {
+                flow.nodes.insert(node_id, node.properties);
+            }
+        }
+
+        flow.ctx_edges
+            .retain(|node_id| flow.nodes.contains_key(node_id));
+        flow.edges.retain(|edge| flow.nodes.contains_key(&edge.to));
+
+        Some(flow)
+    }
+
+    /// flows the app lists, not run copies or deleted graphs
+    pub fn is_flow(&self) -> bool {
+        self.properties.contains_key(FLOW_GRAPH_MARKER)
+            && !self.properties.contains_key("DELETED_GRAPH_MARKER")
+    }
+
+    pub fn name(&self) -> &str {
+        self.properties
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+    }
+
+    pub fn node_ids(&self) -> HashSet<NodeId> {
+        self.nodes.keys().copied().collect()
+    }
+
+    pub fn command_name(&self, node_id: NodeId) -> Option<&str> {
+        self.nodes.get(&node_id)?.get(COMMAND_NAME_MARKER)?.as_str()
+    }
+
+    /// network of the context node, devnet when the graph has none
+    pub fn solana_net(&self) -> SolanaNet {
+        if let Some(endpoint_cfg) = self.solana_endpoint() {
+            return endpoint_cfg.solana_net;
+        }
+
+        self.ctx_node
+            .as_ref()
+            .and_then(|(_, props)| props.get(CTX_MARKER))
+            .and_then(|config| serde_json::from_value::<solana::Config>(config.clone()).ok())
+            .map_or(SolanaNet::Devnet, |config| config.solana_net.into())
+    }
+
+    /// localnet or custom endpoint set on the context node, see Model::change_solana_net
+    pub fn solana_endpoint(&self) -> Option<SolanaEndpointConfig> {
+        self.ctx_node
+            .as_ref()
+            .and_then(|(_, props)| props.get(SOLANA_ENDPOINT_MARKER))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+    }
+
+    /// max SOL a run can transfer, see Model::set_spending_cap
+    pub fn spending_cap(&self) -> Option<f64> {
+        self.properties
+            .get(SPENDING_CAP_MARKER)
+            .and_then(JsonValue::as_f64)
+    }
+
+    /// text of a const node, e.g. { "Const": { "String": "..." } }
+    pub fn text(&self, node_id: NodeId) -> &str {
+        self.nodes
+            .get(&node_id)
+            .and_then(|props| props.get(TEXT_MARKER))
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+    }
+
+    /// ADDITIONAL_DATA_MARKER of a const node, e.g. "Seed Phrase"
+    pub fn kind(&self, node_id: NodeId) -> &str {
+        self.nodes
+            .get(&node_id)
+            .and_then(|props| props.get(ADDITIONAL_DATA_MARKER))
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+    }
+
+    /// text of a const node deployed on the network, see Model::set_const_network_value
+    pub fn network_text(&self, node_id: NodeId, solana_net: SolanaNet) -> Option<&str> {
+        self.nodes
+            .get(&node_id)?
+            .get(NETWORK_VALUES_MARKER)?
+            .get(solana_net.name())?
+            .as_str()
+    }
+
+    /// const with values per network but none for this one
+    pub fn is_missing_network_value(&self, node_id: NodeId, solana_net: SolanaNet) -> bool {
+        let has_network_values = self
+            .nodes
+            .get(&node_id)
+            .map_or(false, |props| props.contains_key(NETWORK_VALUES_MARKER));
+        has_network_values && self.network_text(node_id, solana_net).is_none()
+    }
+
+    /// parameters given at deploy time, by name
+    pub fn flow_inputs(&self) -> Vec<FlowInput> {
+        let mut inputs: Vec<FlowInput> = self
+            .nodes
+            .iter()
+            .filter_map(|(node_id, props)| {
+                let name = props.get(FLOW_INPUT_MARKER)?.as_str()?;
+                Some(FlowInput {
+                    node_id: node_id.0.to_string(),
+                    name: name.to_owned(),
+                    kind: self.kind(*node_id).to_owned(),
+                    default: const_text_payload(self.text(*node_id)),
+                })
+            })
+            .collect();
+        inputs.sort_by(|a, b| a.name.cmp(&b.name));
+        inputs
+    }
+
+    /// values of inputs set on the node, { input label: json of a sunshine_solana::Value }
+    pub fn literals(&self, node_id: NodeId) -> HashMap<String, String> {
+        self.nodes
+            .get(&node_id)
+            .and_then(|props| props.get(INPUT_LITERALS_MARKER))
+            .and_then(|literals| serde_json::from_value(literals.clone()).ok())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// parameter name of a const node whose value is given at deploy time
+pub const FLOW_INPUT_MARKER: &str = "FLOW_INPUT_MARKER";
+
+/// parameter declared by a flow, typed by the kind of its const node
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlowInput {
+    pub node_id: String,
+    pub name: String,
+    pub kind: String,               // ADDITIONAL_DATA_MARKER of the const
+    pub default: Option<JsonValue>, // value on the canvas, None when required
+}
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("input name is empty".to_owned());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "input name {} can only contain letters, digits, - and _",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// const text with its payload replaced by the value given at deploy time,
+/// keeping the variant of the canvas value, e.g. { "Const": { "U64": 1 } }
+pub fn with_payload(text: &str, value: &JsonValue) -> Result<String, String> {
+    let mut command: JsonValue = if text.is_empty() {
+        serde_json::json!({ "Const": { "String": "" } })
+    } else {
+        serde_json::from_str(text).map_err(|e| e.to_string())?
+    };
+
+    let payload = payload_mut(&mut command).ok_or("const has no value to replace")?;
+    *payload = match (&*payload, value) {
+        (JsonValue::String(_), JsonValue::String(_)) => value.clone(),
+        (JsonValue::String(_), JsonValue::Number(_) | JsonValue::Bool(_)) => {
+            JsonValue::String(value.to_string())
+        }
+        (JsonValue::Number(_), JsonValue::String(number)) => {
+            serde_json::from_str(number.trim()).map_err(|_| "expected a number".to_owned())?
+        }
+        (JsonValue::Bool(_), JsonValue::String(flag)) => match flag.as_str() {
+            "true" => JsonValue::Bool(true),
+            "false" => JsonValue::Bool(false),
+            _ => return Err("expected true or false".to_owned()),
+        },
+        (JsonValue::String(_), _) => JsonValue::String(value.to_string()), // json consts
+        _ => value.clone(),
+    };
+
+    Ok(serde_json::to_string(&command).unwrap())
+}
+
+// { "Const": { "U64": 1 } } -> 1
+fn payload_mut(value: &mut JsonValue) -> Option<&mut JsonValue> {
+    if matches!(value, JsonValue::Object(map) if map.len() == 1) {
+        return payload_mut(value.as_object_mut()?.values_mut().next()?);
+    }
+
+    match value {
+        JsonValue::Object(_) | JsonValue::Array(_) | JsonValue::Null => None,
+        _ => Some(value),
+    }
+}
+
+#[test]
+fn flow_input_keeps_const_variant() {
+    use serde_json::json;
+
+    assert_eq!(
+        with_payload(r#"{"Const":{"U64":1}}"#, &json!("5")).unwrap(),
+        r#"{"Const":{"U64":5}}"#
+    );
+    assert_eq!(
+        with_payload(r#"{"Const":{"String":"a"}}"#, &json!(2.5)).unwrap(),
+        r#"{"Const":{"String":"2.5"}}"#
+    );
+    assert_eq!(
+        with_payload("", &json!("b")).unwrap(),
+        r#"{"Const":{"String":"b"}}"#
+    );
+    assert!(with_payload(r#"{"Const":{"Bool":true}}"#, &json!("yes")).is_err());
+    assert!(validate_name("mint amount").is_err());
+}
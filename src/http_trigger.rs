@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sunshine_core::store::Datastore;
+use tokio::sync::{oneshot, Notify};
+use uuid::Uuid;
+
+use crate::flow_context::{find_log_graph, wait_for_run, ActiveRun, RunRequest, RunSender};
+use crate::model::{GraphId, NodeId};
+use crate::run_outputs;
+use crate::view::RunStateView;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// print node whose input is the response body of POST /flows/{graph_id}/run_sync
+pub const RESPONSE_MARKER: &str = "RESPONSE_MARKER";
+
+const MIN_TOKEN_LEN: usize = 16;
+
+/// opt-in server on localhost, requests need `Authorization: Bearer <token>`
+///
+/// POST /flows/{graph_id}/run  body: { "input name": value }  ->  { "run_id" }
+/// POST /flows/{graph_id}/run_sync  ->  input of the response node, once the run is done
+/// GET  /runs/{run_id}  ->  state, inputs and outputs of each node
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpTriggerConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+    pub sync_timeout_secs: u64, // run_sync answers 504 after
+}
+
+impl Default for HttpTriggerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8787,
+            token: String::new(),
+            sync_timeout_secs: 30,
+        }
+    }
+}
+
+impl HttpTriggerConfig {
+    /// disabled when the file doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| e.to_string()),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.port == 0 {
+            return Err("port must be set".to_owned());
+        }
+        if self.sync_timeout_secs == 0 {
+            return Err("sync timeout must be at least a second".to_owned());
+        }
+        if self.token.len() < MIN_TOKEN_LEN {
+            return Err(format!(
+                "token must be at least {} characters",
+                MIN_TOKEN_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct TriggerContext {
+    pub db: Arc<dyn Datastore>,
+    pub runs: Arc<DashMap<Uuid, ActiveRun>>, // by run id
+    pub sender: RunSender,                   // deploys with the checks of the app
+    pub run_finished: Arc<Notify>,           // a run succeeded, failed or was canceled
+    pub config: HttpTriggerConfig,
+}
+
+pub async fn serve(port: u16, ctx: TriggerContext, shutdown: oneshot::Receiver<()>) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    let make_service = make_service_fn(move |_| {
+        let ctx = ctx.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(ctx.clone(), req))) }
+    });
+
+    let server = match Server::try_bind(&addr) {
+        Ok(builder) => builder.serve(make_service),
+        Err(e) => {
+            eprintln!("error while starting http trigger on {}: {}", addr, e);
+            return;
+        }
+    };
+
+    let server = server.with_graceful_shutdown(async {
+        shutdown.await.ok();
+    });
+    if let Err(e) = server.await {
+        eprintln!("http trigger error: {}", e);
+    }
+}
+
+async fn handle(ctx: TriggerContext, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let authorization = req
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !is_authorized(authorization, &ctx.config.token) {
+        return Ok(json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({ "error": "invalid token" }),
+        ));
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().trim_matches('/').to_owned();
+    let segments: Vec<&str> = path.split('/').collect();
+
+    let result = match (method, segments.as_slice()) {
+        (Method::POST, ["flows", graph_id, "run"]) => {
+            run_flow(&ctx, graph_id, req.into_body()).await
+        }
+        (Method::POST, ["flows", graph_id, "run_sync"]) => {
+            return Ok(run_flow_sync(&ctx, graph_id, req.into_body()).await)
+        }
+        (Method::GET, ["runs", run_id]) => run_state(&ctx, run_id).await,
+        _ => Err((StatusCode::NOT_FOUND, "not found".to_owned())),
+    };
+
+    Ok(match result {
+        Ok(body) => json_response(StatusCode::OK, body),
+        Err((status, e)) => json_response(status, json!({ "error": e })),
+    })
+}
+
+async fn run_flow(
+    ctx: &TriggerContext,
+    graph_id: &str,
+    body: Body,
+) -> Result<JsonValue, (StatusCode, String)> {
+    let (_, run_id) = trigger(ctx, graph_id, body).await?;
+    Ok(json!({ "run_id": run_id.to_string() }))
+}
+
+// waits for the run and answers with the input of the response node,
+// 502 with the errors when a node failed
+async fn run_flow_sync(ctx: &TriggerContext, graph_id: &str, body: Body) -> Response<Body> {
+    let error = |status, e: String| json_response(status, json!({ "error": e }));
+
+    let response_node_id = match Uuid::parse_str(graph_id) {
+        Ok(graph_id) => response_node(&*ctx.db, graph_id).await,
+        Err(_) => None,
+    };
+    let response_node_id = match response_node_id {
+        Some(response_node_id) => response_node_id,
+        None => {
+            return error(
+                StatusCode::BAD_REQUEST,
+                "flow has no response node".to_owned(),
+            )
+        }
+    };
+
+    let (_, run_id) = match trigger(ctx, graph_id, body).await {
+        Ok(run) => run,
+        Err((status, e)) => return error(status, e),
+    };
+
+    let timeout = tokio::time::Duration::from_secs(ctx.config.sync_timeout_secs);
+    let finished =
+        tokio::time::timeout(timeout, wait_for_run(&ctx.runs, &ctx.run_finished, run_id)).await;
+
+    let run = match finished {
+        Ok(Some(run)) => run,
+        Ok(None) => return error(StatusCode::NOT_FOUND, "run not found".to_owned()),
+        Err(_) => {
+            return json_response(
+                StatusCode::GATEWAY_TIMEOUT,
+                json!({ "error": "flow is still running", "run_id": run_id.to_string() }),
+            )
+        }
+    };
+
+    let log_nodes = match find_log_graph(&*ctx.db, run.deployed_id, run_id).await {
+        Some((log_graph, _)) => match ctx.db.read_graph(log_graph).await {
+            Ok(log_graph) => log_graph.nodes,
+            Err(_) => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    // the run logs the nodes of its copy
+    let node_id = |properties: &Properties| {
+        properties
+            .get("original_node_id")
+            .and_then(JsonValue::as_str)
+            .and_then(|node_id| Uuid::parse_str(node_id).ok())
+            .and_then(|node_id| run.original_node_id(NodeId(node_id)))
+    };
+
+    match run.state {
+        RunStateView::Success => {}
+        RunStateView::Failed => {
+            let failed_nodes: Vec<JsonValue> = log_nodes
+                .iter()
+                .filter_map(|node| {
+                    let error = node.properties.get("state")?.get("Failed")?.get(1)?;
+                    Some(json!({
+                        "node_id": node_id(&node.properties).map(|node_id| node_id.0.to_string()),
+                        "error": error,
+                    }))
+                })
+                .collect();
+            return json_response(
+                StatusCode::BAD_GATEWAY,
+                json!({
+                    "error": "flow failed",
+                    "run_id": run_id.to_string(),
+                    "failed_nodes": failed_nodes,
+                }),
+            );
+        }
+        _ => return error(StatusCode::CONFLICT, "run was canceled".to_owned()),
+    }
+
+    let body = log_nodes
+        .iter()
+        .find(|node| node_id(&node.properties) == Some(NodeId(response_node_id)))
+        .and_then(|node| node.properties.get("__print_output")?.as_str());
+
+    match body {
+        // printed values are json when they can be
+        Some(body) => match serde_json::from_str::<JsonValue>(body) {
+            Ok(body) => json_response(StatusCode::OK, body),
+            Err(_) => Response::builder()
+                .status(StatusCode::OK)
+                .header(hyper::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+                .body(Body::from(body.to_owned()))
+                .unwrap(),
+        },
+        None => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "response node didn't receive a value".to_owned(),
+        ),
+    }
+}
+
+async fn response_node(db: &dyn Datastore, graph_id: Uuid) -> Option<Uuid> {
+    let graph = db.read_graph(graph_id).await.ok()?;
+    graph
+        .nodes
+        .iter()
+        .find(|node| node.properties.contains_key(RESPONSE_MARKER))
+        .map(|node| node.node_id)
+}
+
+// deploys the flow as stored in the db, returns the run id
+async fn trigger(
+    ctx: &TriggerContext,
+    graph_id: &str,
+    body: Body,
+) -> Result<(GraphId, Uuid), (StatusCode, String)> {
+    let graph_id = Uuid::parse_str(graph_id)
+        .map_err(|_| (StatusCode::NOT_FOUND, "invalid graph id".to_owned()))?;
+
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let input_values = match serde_json::from_slice(&body) {
+        _ if body.is_empty() => Properties::new(),
+        Ok(JsonValue::Object(input_values)) => input_values,
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "body must be a json object of flow inputs".to_owned(),
+            ))
+        }
+    };
+
+    let request = RunRequest {
+        flow_id: GraphId(graph_id),
+        input_values,
+        trigger_outputs: Properties::new(),
+        unattended: true,
+        session: None,
+        plan: None,
+    };
+    match ctx.sender.deploy(request).await {
+        Ok(run_id) => Ok((GraphId(graph_id), run_id)),
+        Err(e) => Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string())),
+    }
+}
+
+async fn run_state(ctx: &TriggerContext, run_id: &str) -> Result<JsonValue, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, "run not found".to_owned());
+
+    let run_id = Uuid::parse_str(run_id).map_err(|_| not_found())?;
+    let run = ctx
+        .runs
+        .get(&run_id)
+        .map(|run| run.value().clone())
+        .ok_or_else(not_found)?;
+
+    let mut nodes = Vec::new();
+    if let Some((log_graph, _)) = find_log_graph(&*ctx.db, run.deployed_id, run_id).await {
+        let log_graph = ctx
+            .db
+            .read_graph(log_graph)
+            .await
+            .map_err(|_| not_found())?;
+        // the run logs the nodes of its copy
+        let copy_node_id = |properties: &Properties| {
+            properties
+                .get("original_node_id")
+                .and_then(JsonValue::as_str)
+                .and_then(|node_id| Uuid::parse_str(node_id).ok())
+                .map(NodeId)
+        };
+        let logged_inputs: HashMap<NodeId, Properties> = log_graph
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let inputs = node.properties.get("inputs")?.as_object()?.clone();
+                Some((copy_node_id(&node.properties)?, inputs))
+            })
+            .collect();
+        let outputs = run_outputs::captured_outputs(&run.edges, &logged_inputs);
+
+        for node in log_graph.nodes {
+            let copy_id = match copy_node_id(&node.properties) {
+                Some(copy_id) => copy_id,
+                None => continue,
+            };
+            // consts and taps of the copy aren't nodes of the graph
+            let node_id = match run.original_node_id(copy_id) {
+                Some(node_id) => node_id,
+                None => continue,
+            };
+            let node_outputs = outputs
+                .get(&copy_id)
+                .map(run_outputs::redact_all)
+                .unwrap_or_default();
+            nodes.push(json!({
+                "node_id": node_id.0.to_string(),
+                "state": node.properties.get("state"),
+                "print_output": node.properties.get("__print_output"),
+                "outputs": node_outputs,
+            }));
+        }
+    }
+
+    Ok(json!({
+        "run_id": run_id.to_string(),
+        "graph_id": run.flow_id.0.to_string(),
+        "network": run.network,
+        "state": format!("{:?}", run.state),
+        "inputs": run.inputs,
+        "nodes": nodes,
+    }))
+}
+
+fn json_response(status: StatusCode, body: JsonValue) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+// compares the whole token, whatever the first mismatch
+fn is_authorized(authorization: Option<&str>, token: &str) -> bool {
+    let given = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(given) => given.trim(),
+        None => return false,
+    };
+
+    !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[test]
+fn http_trigger_token() {
+    let token = "0123456789abcdef";
+    assert!(is_authorized(Some("Bearer 0123456789abcdef"), token));
+    assert!(!is_authorized(Some("Bearer 0123456789abcdeF"), token));
+    assert!(!is_authorized(Some("0123456789abcdef"), token));
+    assert!(!is_authorized(None, token));
+    assert!(!is_authorized(Some("Bearer "), ""));
+
+    let mut config = HttpTriggerConfig {
+        enabled: true,
+        ..HttpTriggerConfig::default()
+    };
+    assert!(config.validate().is_err());
+    config.token = token.to_owned();
+    assert!(config.validate().is_ok());
+}
@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     event::{Coords, Event},
-    model::{Model, NodeId, PortId},
+    model::{EdgeId, Model, NodeId, PortId},
     state::{Transform, UiState},
 };
 
@@ -60,10 +60,12 @@ impl From<MouseSwitch> for Switch {
 pub enum BasicAppEventBuilder {
     Unselect,
     RemoveNodes,
+    RemoveEdges,
     CancelSelection,
     CancelTransformMove,
     CancelNodeMove,
     CancelEdge,
+    CancelEdgeRewire,
     //StartCommandInput,
     //ApplyCommandInput,
     //ModifyCommandInput,
@@ -75,6 +77,8 @@ pub enum PointerAppEventBuilder {
     Unselect,
     SelectNode,
     AddOrRemoveNodeFromSelection,
+    SelectEdge,
+    AddOrRemoveEdgeFromSelection,
     CreateNode,
     EditNode,
     RemoveNodes,
@@ -107,6 +111,13 @@ pub enum PointerAppEventBuilder {
     CancelEdge,
     ContinueEdge,
     //
+    MaybeStartEdgeRewire,
+    NotAEdgeRewire,
+    StartEdgeRewire,
+    EndEdgeRewire,
+    CancelEdgeRewire,
+    ContinueEdgeRewire,
+    //
     ScrollZoom,
     ScrollMoveScreen,
     //StartCommandInput,
@@ -123,6 +134,9 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
     let mut is_create_node_used = false;
     let mut is_edit_node_used = false;
     let mut is_remove_nodes_used = false;
+    let mut is_select_edge_used = false;
+    let mut is_add_edge_to_selection_used = false;
+    let mut is_remove_edges_used = false;
 
     let mut is_maybe_start_selection_used = false;
     let mut is_not_a_selection_used = false;
@@ -152,6 +166,13 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
     let mut is_cancel_edge_used = false;
     let mut is_continue_edge_used = false;
 
+    let mut is_maybe_start_edge_rewire_used = false;
+    let mut is_not_a_edge_rewire_used = false;
+    let mut is_start_edge_rewire_used = false;
+    let mut is_end_edge_rewire_used = false;
+    let mut is_cancel_edge_rewire_used = false;
+    let mut is_continue_edge_rewire_used = false;
+
     let mut is_start_command_input_used = false;
     let mut is_apply_command_input_used = false;
     let mut is_modify_command_input_used = false;
@@ -168,6 +189,9 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
             Event::CreateNode(_) => is_create_node_used = true,
             Event::EditNode(_) => is_edit_node_used = true,
             Event::RemoveNodes(_) => is_remove_nodes_used = true,
+            Event::SelectEdge(_) => is_select_edge_used = true,
+            Event::AddOrRemoveEdgeToSelection(_) => is_add_edge_to_selection_used = true,
+            Event::RemoveEdges(_) => is_remove_edges_used = true,
             //
             Event::MaybeStartSelection(_) => is_maybe_start_selection_used = true,
             Event::NotASelection => is_not_a_selection_used = true,
@@ -197,6 +221,13 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
             Event::CancelEdge(_) => is_cancel_edge_used = true,
             Event::ContinueEdge(_, _) => is_continue_edge_used = true,
             //
+            Event::MaybeStartEdgeRewire(_, _) => is_maybe_start_edge_rewire_used = true,
+            Event::NotAEdgeRewire => is_not_a_edge_rewire_used = true,
+            Event::StartEdgeRewire(_, _, _) => is_start_edge_rewire_used = true,
+            Event::EndEdgeRewire(_, _, _) => is_end_edge_rewire_used = true,
+            Event::CancelEdgeRewire(_) => is_cancel_edge_rewire_used = true,
+            Event::ContinueEdgeRewire(_, _, _) => is_continue_edge_rewire_used = true,
+            //
             Event::ScrollZoom(_, _, _) => is_scroll_zoom_used = true,
             Event::ScrollMoveScreen(_, _) => is_scroll_move_screen_used = true,
             //
@@ -222,20 +253,37 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
                 && !is_create_node_used
                 && !is_select_node_used
                 && !is_add_node_to_selection_used
+                && !is_select_edge_used
+                && !is_add_edge_to_selection_used
                 && !is_end_or_cancel_move_or_selection
         }
         Event::SelectNode(_) => !command_input && !is_create_node_used,
         Event::AddOrRemoveNodeToSelection(_) => !command_input && !is_create_node_used,
+        // nodes are on top of edges
+        Event::SelectEdge(_) => {
+            !command_input
+                && !is_create_node_used
+                && !is_select_node_used
+                && !is_add_node_to_selection_used
+        }
+        Event::AddOrRemoveEdgeToSelection(_) => {
+            !command_input
+                && !is_create_node_used
+                && !is_select_node_used
+                && !is_add_node_to_selection_used
+        }
         Event::CreateNode(_) => {
             !command_input && !is_edit_node_used && !is_end_or_cancel_move_or_selection
         }
         Event::EditNode(_) => !command_input && !is_end_or_cancel_move_or_selection,
         Event::RemoveNodes(_) => !command_input && !is_end_or_cancel_move_or_selection,
+        Event::RemoveEdges(_) => !command_input && !is_end_or_cancel_move_or_selection,
         //
         Event::MaybeStartSelection(_) => {
             !command_input
                 && !is_maybe_start_node_move_used
                 && !is_maybe_start_edge_used
+                && !is_maybe_start_edge_rewire_used
                 && !is_create_node_used
                 && !is_edit_node_used
         }
@@ -249,15 +297,26 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
         Event::MaybeStartNodeMove(_, _) => {
             !command_input
                 && !is_maybe_start_edge_used
+                && !is_maybe_start_edge_rewire_used
+                && !is_create_node_used
+                && !is_edit_node_used
+        }
+        // ends of selected edges are on top of ports
+        Event::MaybeStartEdge(_) => {
+            !command_input
+                && !is_maybe_start_edge_rewire_used
                 && !is_create_node_used
                 && !is_edit_node_used
         }
-        Event::MaybeStartEdge(_) => !command_input && !is_create_node_used && !is_edit_node_used,
+        Event::MaybeStartEdgeRewire(_, _) => {
+            !command_input && !is_create_node_used && !is_edit_node_used
+        }
 
         Event::StartSelection(_, _) => {
             !command_input
                 && !is_start_node_move_used
                 && !is_start_edge_used
+                && !is_start_edge_rewire_used
                 && !is_create_node_used
                 && !is_edit_node_used
         }
@@ -269,9 +328,21 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
                 && !is_edit_node_used
         }
         Event::StartNodeMove(_, _, _) => {
-            !command_input && !is_start_edge_used && !is_create_node_used && !is_edit_node_used
+            !command_input
+                && !is_start_edge_used
+                && !is_start_edge_rewire_used
+                && !is_create_node_used
+                && !is_edit_node_used
+        }
+        Event::StartEdge(_, _) => {
+            !command_input
+                && !is_start_edge_rewire_used
+                && !is_create_node_used
+                && !is_edit_node_used
+        }
+        Event::StartEdgeRewire(_, _, _) => {
+            !command_input && !is_create_node_used && !is_edit_node_used
         }
-        Event::StartEdge(_, _) => !command_input && !is_create_node_used && !is_edit_node_used,
 
         Event::NotASelection
         | Event::EndSelection(_, _)
@@ -289,6 +360,10 @@ fn filter_by_priority(events: Vec<Event>) -> impl Iterator<Item = Event> {
         | Event::CancelEdge(_)
         | Event::ContinueEdge(_, _)
         | Event::EndEdge(_, _)
+        | Event::NotAEdgeRewire
+        | Event::CancelEdgeRewire(_)
+        | Event::ContinueEdgeRewire(_, _, _)
+        | Event::EndEdgeRewire(_, _, _)
         | Event::ScrollZoom(_, _, _)
         | Event::ScrollMoveScreen(_, _) => true,
         //
@@ -406,6 +481,7 @@ pub struct Context<'a> {
     pub ui_state: &'a UiState,
     pub transform: Transform,
     pub selected_node_ids: &'a HashSet<NodeId>,
+    pub selected_edge_ids: &'a HashSet<EdgeId>,
     pub mapping_kind: MappingKind,
 }
 
@@ -414,6 +490,7 @@ pub struct EventContext<'a> {
     pub model: &'a Model,
     pub ui_state: &'a UiState,
     pub selected_node_ids: &'a HashSet<NodeId>,
+    pub selected_edge_ids: &'a HashSet<EdgeId>,
     pub last_scroll_dx: f64, // FIXME
     pub last_scroll_dy: f64, // FIXME
 }
@@ -575,6 +652,13 @@ fn generate_default_mapping(
                 pointer_data: None,
                 event: BasicAppEventBuilder::CancelEdge,
             }),
+            Binding::Release(SwitchBinding {
+                switch: KeyboardSwitch("Escape".into()),
+                modifiers: Modifiers::new(),
+                timed_data: click,
+                pointer_data: None,
+                event: BasicAppEventBuilder::CancelEdgeRewire,
+            }),
             /*Binding::Release(SwitchBinding {
                 switch: KeyboardSwitch("Escape".into()),
                 modifiers: Modifiers::new(),
@@ -595,6 +679,19 @@ fn generate_default_mapping(
                 pointer_data: None,
                 event: BasicAppEventBuilder::RemoveNodes,
             }),
+            Binding::Release(SwitchBinding {
+                switch: KeyboardSwitch("Delete".into()),
+                modifiers: {
+                    let mut modifiers = Modifiers::new();
+                    modifiers
+                        .on_press_event(Switch::Keyboard(KeyboardSwitch("Control Left".to_owned())))
+                        .unwrap();
+                    modifiers
+                },
+                timed_data: dbl_click, // FIXME
+                pointer_data: None,
+                event: BasicAppEventBuilder::RemoveEdges,
+            }),
         ]
         .into_iter()
         .collect(),
@@ -623,6 +720,20 @@ fn generate_default_mapping(
                 pointer_data: None,
                 event: PointerAppEventBuilder::AddOrRemoveNodeFromSelection,
             }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: click,
+                pointer_data: None,
+                event: PointerAppEventBuilder::SelectEdge,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: shift_modifiers.clone(),
+                timed_data: click,
+                pointer_data: None,
+                event: PointerAppEventBuilder::AddOrRemoveEdgeFromSelection,
+            }),
             // CREATE NODE
             Binding::Release(SwitchBinding {
                 switch: lmb,
@@ -1174,6 +1285,96 @@ fn generate_default_mapping(
                 pointer_data: Some(PointerChangeEventData::DragEnd),
                 event: PointerAppEventBuilder::EndEdge,
             }),
+            // EDGE REWIRE
+            Binding::Press(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: (),
+                pointer_data: (),
+                event: PointerAppEventBuilder::MaybeStartEdgeRewire,
+            }),
+            Binding::Coords(CoordsBinding {
+                pointer_data: PointerMoveEventData {
+                    switch: lmb,
+                    kind: PointerMoveEventKind::DragStart,
+                },
+                modifiers: Modifiers::new(),
+                event: PointerAppEventBuilder::StartEdgeRewire,
+            }),
+            Binding::Coords(CoordsBinding {
+                pointer_data: PointerMoveEventData {
+                    switch: lmb,
+                    kind: PointerMoveEventKind::DragMove,
+                },
+                modifiers: Modifiers::new(),
+                event: PointerAppEventBuilder::ContinueEdgeRewire,
+            }),
+            //
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: None,
+                pointer_data: None,
+                event: PointerAppEventBuilder::NotAEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: click, // FIXME
+                pointer_data: None,
+                event: PointerAppEventBuilder::NotAEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: dbl_click, // FIXME
+                pointer_data: None,
+                event: PointerAppEventBuilder::NotAEdgeRewire,
+            }),
+            //
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: None,
+                pointer_data: None,
+                event: PointerAppEventBuilder::CancelEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: click, // FIXME
+                pointer_data: None,
+                event: PointerAppEventBuilder::CancelEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: dbl_click, // FIXME
+                pointer_data: None,
+                event: PointerAppEventBuilder::CancelEdgeRewire,
+            }),
+            //
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: None,
+                pointer_data: Some(PointerChangeEventData::DragEnd),
+                event: PointerAppEventBuilder::EndEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: click, // FIXME
+                pointer_data: Some(PointerChangeEventData::DragEnd),
+                event: PointerAppEventBuilder::EndEdgeRewire,
+            }),
+            Binding::Release(SwitchBinding {
+                switch: lmb,
+                modifiers: Modifiers::new(),
+                timed_data: dbl_click, // FIXME
+                pointer_data: Some(PointerChangeEventData::DragEnd),
+                event: PointerAppEventBuilder::EndEdgeRewire,
+            }),
         ]
         .into_iter()
         .chain(match mapping_name {
@@ -1312,6 +1513,7 @@ impl Input {
             model: context.model,
             ui_state: context.ui_state,
             selected_node_ids: context.selected_node_ids,
+            selected_edge_ids: context.selected_edge_ids,
             last_scroll_dx: event.scroll_delta_x.unwrap_or(0.0),
             last_scroll_dy: event.scroll_delta_y.unwrap_or(0.0),
         };
@@ -1408,6 +1610,7 @@ impl Input {
             model: context.model,
             ui_state: context.ui_state,
             selected_node_ids: context.selected_node_ids,
+            selected_edge_ids: context.selected_edge_ids,
             last_scroll_dx: 0.0,
             last_scroll_dy: 0.0,
         };
@@ -1476,7 +1679,7 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
     fn build(&self, _: &KeyboardCoords, context: EventContext<'_>) -> Option<Event> {
         match self {
             Self::Unselect => {
-                if context.selected_node_ids.is_empty() {
+                if context.selected_node_ids.is_empty() && context.selected_edge_ids.is_empty() {
                     None
                 } else {
                     Some(Event::Unselect)
@@ -1489,6 +1692,13 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
                     Some(Event::RemoveNodes(context.selected_node_ids.clone()))
                 }
             }
+            Self::RemoveEdges => {
+                if context.selected_edge_ids.is_empty() {
+                    None
+                } else {
+                    Some(Event::RemoveEdges(context.selected_edge_ids.clone()))
+                }
+            }
             Self::CancelSelection => match context.ui_state {
                 UiState::Selection(_, _) => Some(Event::CancelSelection),
                 UiState::Default
@@ -1500,6 +1710,8 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelTransformMove => match context.ui_state {
@@ -1513,6 +1725,8 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelNodeMove => match context.ui_state {
@@ -1526,6 +1740,8 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelEdge => match context.ui_state {
@@ -1539,6 +1755,23 @@ impl BuildAppEvent<KeyboardCoords> for BasicAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::MaybeEdge(_)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::CancelEdgeRewire => match context.ui_state {
+                UiState::EdgeRewire(edge_id, _, _) => Some(Event::CancelEdgeRewire(*edge_id)),
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
                 | UiState::CommandInput(_) => None,
             },
             /*Self::StartCommandInput => Some(Event::StartCommandInput("".to_owned())),
@@ -1573,7 +1806,7 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
         match self {
             Self::Unselect => {
                 // TODO: But we probably should cancel click after we handle press for selection
-                if context.selected_node_ids.is_empty() {
+                if context.selected_node_ids.is_empty() && context.selected_edge_ids.is_empty() {
                     // || ctx.next_node_at(coords).is_some()
                     None
                 } else {
@@ -1588,6 +1821,14 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 .model
                 .next_movable_widget_node_at(coords)
                 .map(|(node_id, _)| Event::AddOrRemoveNodeToSelection(*node_id)),
+            Self::SelectEdge => context
+                .model
+                .next_flow_edge_at(coords)
+                .map(|(edge_id, _)| Event::SelectEdge(*edge_id)),
+            Self::AddOrRemoveEdgeFromSelection => context
+                .model
+                .next_flow_edge_at(coords)
+                .map(|(edge_id, _)| Event::AddOrRemoveEdgeToSelection(*edge_id)),
 
             // CREATE
             Self::CreateNode => Some(Event::CreateNode(*coords)),
@@ -1615,6 +1856,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::NotASelection => match context.ui_state {
@@ -1628,6 +1871,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::StartSelection => match context.ui_state {
@@ -1643,6 +1888,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::ContinueSelection => match context.ui_state {
@@ -1658,6 +1905,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::EndSelection => match context.ui_state {
@@ -1673,6 +1922,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelSelection => match context.ui_state {
@@ -1686,6 +1937,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
 
@@ -1701,6 +1954,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::NotATransformMove => match context.ui_state {
@@ -1714,6 +1969,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::StartTransformMove => match context.ui_state {
@@ -1729,6 +1986,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::ContinueTransformMove => match context.ui_state {
@@ -1744,6 +2003,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::EndTransformMove => match context.ui_state {
@@ -1759,6 +2020,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelTransfromMove => match context.ui_state {
@@ -1772,6 +2035,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
 
@@ -1790,6 +2055,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::NotANodeMove => match context.ui_state {
@@ -1803,6 +2070,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::StartNodeMove => match context.ui_state {
@@ -1818,6 +2087,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::ContinueNodeMove => match context.ui_state {
@@ -1833,6 +2104,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::EndNodeMove => match context.ui_state {
@@ -1848,6 +2121,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelNodeMove => match context.ui_state {
@@ -1861,6 +2136,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::MaybeEdge(_)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
 
@@ -1878,6 +2155,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::NotAEdge => match context.ui_state {
@@ -1891,6 +2170,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::StartEdge => match context.ui_state {
@@ -1904,6 +2185,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::Edge(_, _)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::ContinueEdge => match context.ui_state {
@@ -1917,6 +2200,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::MaybeEdge(_)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::EndEdge => match context.ui_state {
@@ -1941,6 +2226,8 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::MaybeEdge(_)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::CancelEdge => match context.ui_state {
@@ -1954,6 +2241,121 @@ impl BuildAppEvent<Coords> for PointerAppEventBuilder {
                 | UiState::NodeMove(_, _)
                 | UiState::MaybeEdge(_)
                 | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::EdgeRewire(_, _, _)
+                | UiState::CommandInput(_) => None,
+            },
+
+            // EDGE REWIRE
+            Self::MaybeStartEdgeRewire => match context.ui_state {
+                UiState::Default | UiState::MaybeEdgeRewire(_, _) => {
+                    context.selected_edge_ids.iter().find_map(|edge_id| {
+                        context
+                            .model
+                            .rewire_anchor_at(edge_id, coords)
+                            .map(|port_id| Event::MaybeStartEdgeRewire(*edge_id, port_id))
+                    })
+                }
+                UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::EdgeRewire(_, _, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::NotAEdgeRewire => match context.ui_state {
+                UiState::MaybeEdgeRewire(_, _) => Some(Event::NotAEdgeRewire),
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::EdgeRewire(_, _, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::StartEdgeRewire => match context.ui_state {
+                UiState::MaybeEdgeRewire(edge_id, port_id) => {
+                    Some(Event::StartEdgeRewire(*edge_id, *port_id, *coords))
+                }
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::EdgeRewire(_, _, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::ContinueEdgeRewire => match context.ui_state {
+                UiState::EdgeRewire(edge_id, port_id, _) => {
+                    Some(Event::ContinueEdgeRewire(*edge_id, *port_id, *coords))
+                }
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::EndEdgeRewire => match context.ui_state {
+                UiState::EdgeRewire(edge_id, port_id, _) => match port_id {
+                    PortId::Input(input_id) => context
+                        .model
+                        .next_output_at(coords)
+                        .map(|(output_id, _)| Event::EndEdgeRewire(*edge_id, *input_id, *output_id))
+                        .or_else(|| Some(Event::CancelEdgeRewire(*edge_id))),
+                    PortId::Output(output_id) => context
+                        .model
+                        .next_input_at(coords)
+                        .map(|(input_id, _)| Event::EndEdgeRewire(*edge_id, *input_id, *output_id))
+                        .or_else(|| Some(Event::CancelEdgeRewire(*edge_id))),
+                },
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
+                | UiState::CommandInput(_) => None,
+            },
+            Self::CancelEdgeRewire => match context.ui_state {
+                UiState::EdgeRewire(edge_id, _, _) => Some(Event::CancelEdgeRewire(*edge_id)),
+                UiState::Default
+                | UiState::MaybeSelection(_)
+                | UiState::Selection(_, _)
+                | UiState::MaybeTransformMove(_)
+                | UiState::TransformMove(_)
+                | UiState::MaybeNodeMove(_, _)
+                | UiState::NodeMove(_, _)
+                | UiState::MaybeEdge(_)
+                | UiState::Edge(_, _)
+                | UiState::UiInput
+                | UiState::MaybeEdgeRewire(_, _)
                 | UiState::CommandInput(_) => None,
             },
             Self::ScrollZoom => Some(Event::ScrollZoom(
@@ -31,16 +31,33 @@
 */
 
 // mod workaround;
+mod address_book;
 mod api;
+mod audit;
 mod command;
+mod cost;
+mod deploy_check;
 mod event;
 mod flow_context;
+mod flow_graph;
+mod flow_input;
+mod http_trigger;
 mod input;
 mod model;
+mod partial_run;
+mod review;
+mod routing;
+mod run_copy;
+mod run_outputs;
+mod seed;
 mod state;
 //pub mod storage;
 mod utils;
+mod validation;
+mod vault;
 mod view;
+mod wallet;
+mod watch_trigger;
 
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
@@ -50,11 +67,16 @@ use std::str::FromStr;
 use api::Response;
 use futures::executor::block_on;
 use model::BasicWidgetKind;
+use model::EdgeRouting;
+use model::ExportOptions;
+use model::FlowEdgeModel;
 use model::GraphId;
+use model::SolanaEndpoint;
 use model::SolanaNet;
 use model::WidgetKind;
 
 use model::WidgetNodeData;
+use seed::SeedConfig;
 use serde::Deserialize;
 
 use serde_json::json;
@@ -67,13 +89,16 @@ use uuid::Uuid;
 
 use event::{Coords, Event};
 use rid::RidStore;
+use view::EdgePoint;
 use view::EdgeView;
 use view::NodeView;
 use view::NodeViewType;
 use view::RunStateView;
 use view::ViewEdgeType;
 use view::{
-    commands_view_map, BookmarkView, Command, DebugData, LastViewChanges, Ratio, Selection, View,
+    commands_view_map, ActiveRunView, AddressEntryView, BookmarkView, Command, DebugData,
+    FlowInputView, LastViewChanges, Ratio, Selection, SolanaEndpointView, VaultEntryView,
+    VaultView, View, WalletKeypairView,
 };
 
 use sunshine_indra::store::generate_uuid_v1;
@@ -224,34 +249,74 @@ impl RidStore<Msg> for Store {
                 rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
             }
             Msg::ChangeSolanaNet(ev) => {
+                // "Devnet" | "Testnet" | "Mainnet" | "Localnet" | { rpc_url, ws_url }
                 let model = self.state.as_mut().unwrap().model_mut();
-                let solana_net = match ev.as_str() {
-                    "Testnet" => SolanaNet::Testnet,
-                    "Devnet" => SolanaNet::Devnet,
-                    "Mainnet" => SolanaNet::Mainnet,
-                    _ => panic!(),
+                let (solana_net, endpoint) = match ev.as_str() {
+                    "Testnet" => (SolanaNet::Testnet, None),
+                    "Devnet" => (SolanaNet::Devnet, None),
+                    "Mainnet" => (SolanaNet::Mainnet, None),
+                    "Localnet" => (SolanaNet::Localnet, Some(SolanaEndpoint::localnet())),
+                    _ => match serde_json::from_str(&ev) {
+                        Ok(endpoint) => (SolanaNet::Custom, Some(endpoint)),
+                        Err(e) => {
+                            rid::post(Confirm::InvalidSolanaNet(req_id, e.to_string()));
+                            return;
+                        }
+                    },
                 };
-                model.change_solana_net(solana_net);
+                model.change_solana_net(solana_net, endpoint);
 
                 self.refresh_ui();
 
                 rid::post(Confirm::ReceivedEvent(req_id, "".to_owned()));
             }
-            Msg::Import(path) => {
-                let model = self.state.as_mut().unwrap().model_mut();
-
-                model.import(&path);
+            Msg::Import(path) => self.import(req_id, &path, None),
+            Msg::ImportEncrypted(path, passphrase) => self.import(req_id, &path, Some(&passphrase)),
+            Msg::Export(path, filename) => {
+                let model = self.state.as_ref().unwrap().model();
 
-                let state = self.state.as_mut().unwrap();
-                state.reset();
-                self.refresh_ui();
-                rid::post(Confirm::LoadGraph(req_id, "".to_owned()));
+                match model.export(path, filename, &ExportOptions::default()) {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::ExportError(req_id, e)),
+                }
             }
-            Msg::Export(path, filename) => {
+            Msg::ExportWithOptions(path, options) => {
+                // { "redact_secrets": bool, "passphrase": string | null }
+                let options: ExportOptions = match serde_json::from_str(&options) {
+                    Ok(options) => options,
+                    Err(e) => {
+                        rid::post(Confirm::ExportError(req_id, e.to_string()));
+                        return;
+                    }
+                };
                 let model = self.state.as_ref().unwrap().model();
 
-                model.export(path, filename);
-                rid::post(Confirm::ReceivedEvent(req_id, "".to_owned()));
+                match model.export(path, String::new(), &options) {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::ExportError(req_id, e)),
+                }
+            }
+            Msg::SetConstNetworkValue(node_id, network, text) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let text = if text.is_empty() { None } else { Some(text) };
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_const_network_value(node_id, &network, text) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::InvalidNetworkValue(req_id, e)),
+                }
+            }
+            Msg::SetConstSecret(node_id, is_secret) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                model.set_const_secret(node_id, is_secret == "true");
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
             }
             Msg::Debug(_ev) => {
                 let model = self.state.as_ref().unwrap().model();
@@ -445,23 +510,310 @@ impl RidStore<Msg> for Store {
                 model.update_const_in_db(node_id, &event.text);
                 // }
 
+                self.refresh_if_const_error_changed(node_id, req_id);
                 rid::post(Confirm::ReceivedEvent(req_id, ev.to_owned()));
             }
-            Msg::GenerateSeedPhrase(_) => {
-                let phrase = crate::model::Model::generate_seed();
+            Msg::GenerateSeedPhrase(config) => {
+                // { word_count, language, passphrase, derivation_path }, "" for defaults
+                let config = parse_seed_config(&config);
+
+                match crate::model::Model::generate_seed(&config) {
+                    Ok(phrase) => rid::post(Confirm::SendSeedPhrase(req_id, phrase)),
+                    Err(e) => rid::post(Confirm::SeedPhraseError(req_id, e)),
+                }
+            }
+            Msg::ValidateSeedPhrase(phrase, config) => {
+                let config = parse_seed_config(&config);
+
+                match crate::model::Model::validate_seed(&phrase, &config) {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::SeedPhraseError(req_id, e)),
+                }
+            }
+            Msg::DeriveSeedAddress(node_id, config) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let config = parse_seed_config(&config);
+                let model = self.state.as_ref().unwrap().model();
 
-                rid::post(Confirm::SendSeedPhrase(req_id, phrase.to_owned()));
+                match model.seed_address(&node_id, &config) {
+                    Ok(pubkey) => rid::post(Confirm::SeedAddress(
+                        req_id,
+                        json!({ "node_id": node_id.0.to_string(), "pubkey": pubkey }).to_string(),
+                    )),
+                    Err(e) => rid::post(Confirm::SeedPhraseError(req_id, e)),
+                }
             }
-            Msg::Deploy(_ev) => {
-                let mut req_id_lock = self.state.as_ref().unwrap().model().req_id.lock().unwrap();
-                *req_id_lock = req_id;
+            Msg::Deploy(input_values) => {
+                // { "input name": value }, "" when the flow has no inputs
+                let input_values = match parse_input_values(&input_values) {
+                    Ok(input_values) => input_values,
+                    Err(e) => {
+                        rid::post(Confirm::DeployError(req_id, e));
+                        return;
+                    }
+                };
+
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                // mainnet spends real SOL, the user confirms what will be signed first
+                if model.solana_net.is_mainnet() {
+                    let review = match model.deploy_review(&input_values) {
+                        Ok(review) => review,
+                        Err(e) => {
+                            rid::post(Confirm::DeployError(req_id, e.to_string()));
+                            return;
+                        }
+                    };
+                    rid::post(Confirm::ReviewDeploy(
+                        req_id,
+                        serde_json::to_string(&review).unwrap(),
+                    ));
+                    model.pending_review = Some(review);
+                    model.pending_input_values = input_values;
+                    return;
+                }
 
-                self.state.as_ref().unwrap().model().deploy();
+                self.deploy(req_id, &input_values);
 
                 // rid::post(Confirm::Deployed(req_id, ev.to_owned()));
             }
+            Msg::RunNode(node_id, input_values) => {
+                self.deploy_partial(req_id, &node_id, false, &input_values);
+            }
+            Msg::RunFromNode(node_id, input_values) => {
+                self.deploy_partial(req_id, &node_id, true, &input_values);
+            }
+            Msg::SetBreakpoint(node_id, is_breakpoint) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_breakpoint(node_id, is_breakpoint == "true") {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::DeployError(req_id, e)),
+                }
+            }
+            Msg::Continue(_) => self.resume_run(req_id, false),
+            Msg::Step(_) => self.resume_run(req_id, true),
+            Msg::ConfirmDeploy(_) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+                let input_values = std::mem::take(&mut model.pending_input_values);
+                let review = match model.deploy_review(&input_values) {
+                    Ok(review) => review,
+                    Err(e) => {
+                        rid::post(Confirm::DeployError(req_id, e.to_string()));
+                        return;
+                    }
+                };
+
+                // graph changed since the review, show the new one
+                if model.pending_review.take().as_ref() != Some(&review) {
+                    rid::post(Confirm::ReviewDeploy(
+                        req_id,
+                        serde_json::to_string(&review).unwrap(),
+                    ));
+                    model.pending_review = Some(review);
+                    model.pending_input_values = input_values;
+                    return;
+                }
+
+                self.deploy(req_id, &input_values);
+            }
+            Msg::CancelDeploy(_) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+                model.pending_review = None;
+                model.pending_input_values.clear();
+
+                rid::post(Confirm::ReceivedEvent(req_id, "".to_owned()));
+            }
+            Msg::QueryAuditLog(filter) => {
+                // { graph_id, from, to }, all optional
+                let filter: audit::AuditFilter = serde_json::from_str(&filter).unwrap();
+                let model = self.state.as_ref().unwrap().model();
+
+                match model.audit_log.lock().unwrap().query(&filter) {
+                    Ok(entries) => rid::post(Confirm::AuditLog(
+                        req_id,
+                        serde_json::to_string(&entries).unwrap(),
+                    )),
+                    Err(e) => rid::post(Confirm::AuditLogError(req_id, e)),
+                }
+            }
+            Msg::ExportAuditLog(path, filter) => {
+                let filter: audit::AuditFilter = serde_json::from_str(&filter).unwrap();
+                let model = self.state.as_ref().unwrap().model();
+
+                let entries = model.audit_log.lock().unwrap().query(&filter);
+                match entries {
+                    Ok(entries) => match std::fs::write(&path, audit::to_csv(&entries)) {
+                        Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                        Err(e) => rid::post(Confirm::AuditLogError(req_id, e.to_string())),
+                    },
+                    Err(e) => rid::post(Confirm::AuditLogError(req_id, e)),
+                }
+            }
+            Msg::SetHttpTrigger(config) => {
+                // { enabled, port, token }
+                let model = self.state.as_mut().unwrap().model_mut();
+                let result = serde_json::from_str(&config)
+                    .map_err(|e| e.to_string())
+                    .and_then(|config| model.set_http_trigger(config));
+
+                match result {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::InvalidHttpTrigger(req_id, e)),
+                }
+            }
+            Msg::SetResponseNode(node_id, is_response) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_response_node(node_id, is_response == "true") {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::InvalidHttpTrigger(req_id, e)),
+                }
+            }
+            Msg::SetWatchTrigger(config) => {
+                // { directory, glob, debounce_ms }, the flow needs a watch_trigger node
+                let model = self.state.as_mut().unwrap().model_mut();
+                let result = match config.as_str() {
+                    "" => model.set_watch_trigger(None),
+                    config => serde_json::from_str(config)
+                        .map_err(|e| e.to_string())
+                        .and_then(|config| model.set_watch_trigger(Some(config))),
+                };
+
+                match result {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::InvalidWatchTrigger(req_id, e)),
+                }
+            }
+            Msg::SetFlowInput(node_id, name) => {
+                // "" turns the input back into a plain const
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let name = Some(name).filter(|name| !name.trim().is_empty());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_flow_input(node_id, name) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::InvalidFlowInput(req_id, e)),
+                }
+            }
+            Msg::RefreshWallet(_) => {
+                // keypairs saved by runs, e.g. generate_keypair
+                let model = self.state.as_mut().unwrap().model_mut();
+                model.refresh_wallet();
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
+            Msg::ImportKeypair(path, label) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.import_keypair(&path, label) {
+                    Ok(_) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::WalletError(req_id, e)),
+                }
+            }
+            Msg::ExportKeypair(node_id, path) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_ref().unwrap().model();
+
+                match model.export_keypair(node_id, &path) {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::WalletError(req_id, e)),
+                }
+            }
+            Msg::SetKeypairLabel(node_id, label) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_keypair_label(node_id, label) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::WalletError(req_id, e)),
+                }
+            }
+            Msg::SetDefaultPayer(node_id) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_default_payer(node_id) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::WalletError(req_id, e)),
+                }
+            }
+            Msg::EstimateCosts(_) => {
+                let model = self.state.as_ref().unwrap().model();
+                let estimate = model.estimate_costs();
+
+                rid::post(Confirm::CostEstimate(
+                    req_id,
+                    serde_json::to_string(&estimate).unwrap(),
+                ));
+            }
+            Msg::SetFeeTable(fee_table) => {
+                // FeeTable json, missing fields use the mainnet defaults
+                let model = self.state.as_mut().unwrap().model_mut();
+                let result = serde_json::from_str(&fee_table)
+                    .map_err(|e| e.to_string())
+                    .and_then(|fee_table| model.set_fee_table(fee_table));
+
+                match result {
+                    Ok(()) => rid::post(Confirm::ReceivedEvent(req_id, "".to_owned())),
+                    Err(e) => rid::post(Confirm::InvalidFeeTable(req_id, e)),
+                }
+            }
+            Msg::SetSpendingCap(spending_cap) => {
+                // SOL, "" removes the cap
+                let spending_cap = match spending_cap.as_str() {
+                    "" => None,
+                    spending_cap => match spending_cap.parse::<f64>() {
+                        Ok(spending_cap) if spending_cap >= 0.0 => Some(spending_cap),
+                        _ => {
+                            rid::post(Confirm::InvalidSpendingCap(
+                                req_id,
+                                format!(
+                                    "spending cap must be an amount of SOL, got {}",
+                                    spending_cap
+                                ),
+                            ));
+                            return;
+                        }
+                    },
+                };
+                let model = self.state.as_mut().unwrap().model_mut();
+                model.set_spending_cap(spending_cap);
+
+                rid::post(Confirm::ReceivedEvent(req_id, "".to_owned()));
+            }
+            Msg::CancelRun(_) => {
+                self.state.as_mut().unwrap().model_mut().cancel_run();
+                self.refresh_ui();
+
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
             Msg::UnDeploy(ev) => {
-                self.state.as_ref().unwrap().model().undeploy();
+                self.state.as_mut().unwrap().model_mut().undeploy();
                 self.refresh_ui();
 
                 rid::post(Confirm::UnDeployed(req_id, ev.to_owned()));
@@ -790,9 +1142,12 @@ impl RidStore<Msg> for Store {
                 //println!("remove nodes {:?}", node_id);
                 let state = self.state.as_mut().unwrap();
 
-                state
+                let removed_edge_ids = state
                     .model_mut()
                     .remove_node(NodeId(Uuid::parse_str(&node_id).unwrap()));
+                for edge_id in removed_edge_ids {
+                    state.selected_edge_ids.remove(&edge_id);
+                }
                 self.refresh_ui();
                 rid::post(Confirm::RemoveNode(req_id, node_id));
             }
@@ -868,6 +1223,134 @@ impl RidStore<Msg> for Store {
                 state
                     .model_mut()
                     .update_const_additional_in_db(child_id, additional_data);
+
+                self.refresh_if_const_error_changed(child_id, req_id);
+            }
+            Msg::SetEdgeWaypoints(edge_id, waypoints) => {
+                // waypoints: [{ "x": 0.0, "y": 0.0 }, ...] in canvas coords
+                let edge_id = model::EdgeId(Uuid::parse_str(&edge_id).unwrap());
+                let waypoints: Vec<Coords> = serde_json::from_str(&waypoints).unwrap();
+
+                let state = self.state.as_mut().unwrap();
+                state
+                    .model_mut()
+                    .set_flow_edge_waypoints(edge_id, waypoints);
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
+            Msg::SetEdgeRouting(edge_id, routing) => {
+                let edge_id = model::EdgeId(Uuid::parse_str(&edge_id).unwrap());
+                let routing = match routing.as_str() {
+                    "direct" => EdgeRouting::Direct,
+                    "orthogonal" => EdgeRouting::Orthogonal,
+                    routing => {
+                        rid::post(Confirm::InvalidEdgeRouting(
+                            req_id,
+                            format!("unknown edge routing: {}", routing),
+                        ));
+                        return;
+                    }
+                };
+
+                let state = self.state.as_mut().unwrap();
+                state.model_mut().set_flow_edge_routing(edge_id, routing);
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
+            Msg::SetInputLiteral(input_id, literal) => {
+                let input_id = model::InputId(Uuid::parse_str(&input_id).unwrap());
+                let literal = if literal.is_empty() {
+                    None
+                } else {
+                    Some(literal)
+                };
+
+                let state = self.state.as_mut().unwrap();
+                match state.model_mut().set_input_literal(input_id, literal) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::InvalidInputLiteral(req_id, e)),
+                }
+            }
+            Msg::UnlockVault(password) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+                if let Some(e) = model.take_vault_error() {
+                    rid::post(Confirm::VaultError(req_id, e));
+                    return;
+                }
+
+                let result = model.vault.lock().unwrap().unlock(&password);
+                match result {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::VaultError(req_id, e.to_string())),
+                }
+            }
+            Msg::LockVault(_) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+                model.vault.lock().unwrap().lock();
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
+            Msg::MoveConstToVault(node_id, label) => {
+                let node_id = NodeId(Uuid::parse_str(&node_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+                if let Some(e) = model.take_vault_error() {
+                    rid::post(Confirm::VaultError(req_id, e));
+                    return;
+                }
+
+                match model.move_const_to_vault(node_id, label) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::VaultError(req_id, e.to_string())),
+                }
+            }
+            Msg::RemoveVaultSecret(vault_id) => {
+                let vault_id = vault::VaultId(Uuid::parse_str(&vault_id).unwrap());
+                let model = self.state.as_mut().unwrap().model_mut();
+                if let Some(e) = model.take_vault_error() {
+                    rid::post(Confirm::VaultError(req_id, e));
+                    return;
+                }
+
+                let result = model.vault.lock().unwrap().remove_secret(vault_id);
+                match result {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::VaultError(req_id, e.to_string())),
+                }
+            }
+            Msg::SetAddressEntry(entry) => {
+                // { name, pubkey, networks: { "mainnet": pubkey }, notes }
+                let entry = serde_json::from_str(&entry).unwrap();
+                let model = self.state.as_mut().unwrap().model_mut();
+
+                match model.set_address_entry(entry) {
+                    Ok(()) => {
+                        self.refresh_ui();
+                        rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                    }
+                    Err(e) => rid::post(Confirm::AddressBookError(req_id, e)),
+                }
+            }
+            Msg::RemoveAddressEntry(name) => {
+                let model = self.state.as_mut().unwrap().model_mut();
+                model.remove_address_entry(&name);
+
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
             }
             Msg::SetMappingKind(kind) => {
                 let state = self.state.as_mut().unwrap();
@@ -975,8 +1458,14 @@ pub enum Msg {
     // SetText2(String),     // { node_id, text }
     ApplyCommand(String, String),
     ApplyAutocomplete(String),
-    Deploy(String),
+    Deploy(String),                // flow input values, { "name": value }
+    RunNode(String, String), // node_id, flow input values, upstream nodes give their latest outputs
+    RunFromNode(String, String), // node_id, flow input values, runs the downstream nodes too
+    SetBreakpoint(String, String), // command node_id, "true" | "false"
+    Continue(String),        // runs a paused deploy to the next breakpoint
+    Step(String),            // runs only the nodes a deploy is paused at
     UnDeploy(String),
+    CancelRun(String),
     Request(String), //
     Refresh(String),
     Import(String),
@@ -997,6 +1486,37 @@ pub enum Msg {
     SetAdditionalData(String, String),
     RenameGraph(String, String),
     DeleteGraph(String),
+    SetEdgeWaypoints(String, String), // edge_id, [{ x, y }]
+    SetEdgeRouting(String, String),   // edge_id, "direct" | "orthogonal"
+    SetInputLiteral(String, String),  // input_id, serialized value or "" to remove
+    UnlockVault(String),              // password, creates the vault the first time
+    LockVault(String),
+    MoveConstToVault(String, String),   // node_id, label
+    RemoveVaultSecret(String),          // vault id
+    ImportEncrypted(String, String),    // path, passphrase
+    ExportWithOptions(String, String),  // path, { redact_secrets, passphrase }
+    SetConstSecret(String, String),     // node_id, "true" | "false"
+    ValidateSeedPhrase(String, String), // phrase, seed config
+    DeriveSeedAddress(String, String),  // node_id, seed config
+    SetAddressEntry(String),            // address entry json, replaces an entry with the same name
+    RemoveAddressEntry(String),         // name
+    ConfirmDeploy(String),              // deploy the reviewed mainnet flow
+    CancelDeploy(String),
+    SetSpendingCap(String),         // SOL, "" to remove
+    QueryAuditLog(String),          // { graph_id, from, to }
+    ExportAuditLog(String, String), // csv path, { graph_id, from, to }
+    EstimateCosts(String),
+    SetFeeTable(String),             // FeeTable json
+    SetFlowInput(String, String),    // node_id, input name or "" to remove
+    SetHttpTrigger(String),          // HttpTriggerConfig json
+    SetResponseNode(String, String), // print node_id, "true" | "false"
+    SetWatchTrigger(String),         // WatchTriggerConfig json, "" to stop watching
+    RefreshWallet(String),
+    ImportKeypair(String, String), // path of a solana-keygen json file, label
+    ExportKeypair(String, String), // node_id, path
+    SetKeypairLabel(String, String), // node_id, label
+    SetDefaultPayer(String),       // node_id
+    SetConstNetworkValue(String, String, String), // node_id, "devnet" | "testnet" | "mainnet", const text or "" to remove
 }
 
 // Required Reply
@@ -1021,6 +1541,28 @@ pub enum Confirm {
     DeleteGraph(u64, String),
     RefreshNode(u64, String),
     RefreshDraggedEdge(u64, String),
+    InvalidInputLiteral(u64, String),
+    InvalidEdgeRouting(u64, String),
+    VaultError(u64, String),
+    DeployError(u64, String),
+    ImportError(u64, String),
+    ExportError(u64, String),
+    RedactedConsts(u64, String), // node ids of consts to enter again after import
+    SeedPhraseError(u64, String),
+    SeedAddress(u64, String), // { node_id, pubkey }
+    AddressBookError(u64, String),
+    InvalidNetworkValue(u64, String),
+    InvalidSpendingCap(u64, String),
+    InvalidSolanaNet(u64, String),
+    ReviewDeploy(u64, String), // DeployReview, answered with ConfirmDeploy or CancelDeploy
+    AuditLog(u64, String),     // [AuditEntry]
+    AuditLogError(u64, String),
+    CostEstimate(u64, String), // { nodes: [{ node_id, command_name, fee_lamports, rent_lamports }], total_lamports, total_sol }
+    InvalidFeeTable(u64, String),
+    WalletError(u64, String),
+    InvalidFlowInput(u64, String),
+    InvalidHttpTrigger(u64, String),
+    InvalidWatchTrigger(u64, String),
 }
 
 impl Store {
@@ -1053,6 +1595,19 @@ impl Store {
                     self.refresh_ui();
                     rid::post(Confirm::RefreshUI(req_id, "".to_owned()))
                 }
+                Event::SelectEdge(edge_id) => {
+                    state.clear_selection();
+
+                    state.add_edge_to_selection(edge_id);
+                    self.refresh_selection();
+                    rid::post(Confirm::RefreshUI(req_id, "selection".to_owned()))
+                }
+                Event::AddOrRemoveEdgeToSelection(edge_id) => {
+                    state.add_or_remove_edge_from_selection(edge_id);
+
+                    self.refresh_ui();
+                    rid::post(Confirm::RefreshUI(req_id, "".to_owned()))
+                }
 
                 // CRUD
                 Event::CreateNode(coords) => {
@@ -1066,7 +1621,9 @@ impl Store {
                 Event::RemoveNodes(removable_node_ids) => {
                     // println!("remove nodes {:?}", removable_node_ids);
                     for node_id in removable_node_ids {
-                        state.model_mut().remove_node(node_id);
+                        for edge_id in state.model_mut().remove_node(node_id) {
+                            state.selected_edge_ids.remove(&edge_id);
+                        }
                         let node_id = node_id.0.to_string();
                         rid::post(Confirm::RemoveNode(req_id, node_id))
                     }
@@ -1281,17 +1838,73 @@ impl Store {
                 }
                 Event::EndEdge(port_id, output_id) => {
                     // println!("Connect Edge {:?} {:?}", port_id, output_id);
-                    state
+                    // toggled off or replaced on the input
+                    let (_, removed_edge_id) = state
                         .model_mut()
                         .add_or_remove_flow_edge(port_id, output_id);
+                    if let Some(removed_edge_id) = removed_edge_id {
+                        state.selected_edge_ids.remove(&removed_edge_id);
+                    }
                     state.ui_state = UiState::Default; // Question? should it be last?
                     self.refresh_ui();
+                    self.last_view_changes.replaced_flow_edges_ids = removed_edge_id
+                        .map(|edge_id| edge_id.0.to_string())
+                        .into_iter()
+                        .collect();
                     rid::post(Confirm::RefreshUI(req_id, "end_edge".to_owned()));
                 }
                 Event::CancelEdge(_) => {
                     state.ui_state = UiState::Default;
                     self.refresh_ui();
                     rid::post(Confirm::RefreshUI(req_id, "end_edge".to_owned()));
+                }
+                Event::RemoveEdges(removable_edge_ids) => {
+                    for edge_id in removable_edge_ids {
+                        state.model_mut().remove_flow_edge(edge_id);
+                        state.selected_edge_ids.remove(&edge_id);
+                    }
+                    self.refresh_ui();
+                    rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+                }
+                Event::MaybeStartEdgeRewire(edge_id, port_id) => {
+                    state.ui_state = UiState::MaybeEdgeRewire(edge_id, port_id);
+                }
+                Event::NotAEdgeRewire => {
+                    state.ui_state = UiState::Default;
+                }
+                Event::StartEdgeRewire(edge_id, port_id, coords) => {
+                    state.ui_state = UiState::EdgeRewire(edge_id, port_id, coords);
+                    // hides the edge being rewired
+                    self.refresh_ui();
+                    rid::post(Confirm::RefreshDraggedEdge(req_id, "start".to_owned()));
+                }
+                Event::ContinueEdgeRewire(edge_id, port_id, coords) => {
+                    state.ui_state = UiState::EdgeRewire(edge_id, port_id, coords);
+                    self.refresh_ui_flow_edges();
+                    rid::post(Confirm::RefreshDraggedEdge(req_id, "".to_owned()));
+                }
+                Event::EndEdgeRewire(edge_id, input_id, output_id) => {
+                    let (new_edge_id, replaced_edge_id) = state
+                        .model_mut()
+                        .rewire_flow_edge(edge_id, input_id, output_id);
+                    if let Some(replaced_edge_id) = replaced_edge_id {
+                        state.selected_edge_ids.remove(&replaced_edge_id);
+                    }
+                    if state.selected_edge_ids.remove(&edge_id) {
+                        state.selected_edge_ids.insert(new_edge_id);
+                    }
+                    state.ui_state = UiState::Default;
+                    self.refresh_ui();
+                    self.last_view_changes.replaced_flow_edges_ids = replaced_edge_id
+                        .map(|edge_id| edge_id.0.to_string())
+                        .into_iter()
+                        .collect();
+                    rid::post(Confirm::RefreshUI(req_id, "end_edge".to_owned()));
+                }
+                Event::CancelEdgeRewire(_) => {
+                    state.ui_state = UiState::Default;
+                    self.refresh_ui();
+                    rid::post(Confirm::RefreshUI(req_id, "end_edge".to_owned()));
                 } /*Event::StartCommandInput(command) | Event::ModifyCommandInput(command) => {
                 println!("command input: {}", &command);
                 state.ui_state = UiState::CommandInput(command);
@@ -1381,6 +1994,106 @@ impl Store {
         }
     */
 
+    fn deploy(&mut self, req_id: u64, input_values: &serde_json::Map<String, serde_json::Value>) {
+        let model = self.state.as_mut().unwrap().model_mut();
+        *model.req_id.lock().unwrap() = req_id;
+
+        // pauses before the first breakpoint
+        let result = if model.breakpoints.is_empty() {
+            model.deploy(input_values)
+        } else {
+            model.start_debug_run(input_values)
+        };
+        if let Err(e) = result {
+            rid::post(Confirm::DeployError(req_id, e.to_string()));
+        }
+    }
+
+    fn resume_run(&mut self, req_id: u64, step: bool) {
+        let model = self.state.as_mut().unwrap().model_mut();
+        *model.req_id.lock().unwrap() = req_id;
+
+        match model.resume_run(step) {
+            Ok(()) => {
+                self.refresh_ui();
+                rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+            }
+            Err(e) => rid::post(Confirm::DeployError(req_id, e.to_string())),
+        }
+    }
+
+    fn deploy_partial(
+        &mut self,
+        req_id: u64,
+        node_id: &str,
+        with_downstream: bool,
+        input_values: &str,
+    ) {
+        let input_values = match parse_input_values(input_values) {
+            Ok(input_values) => input_values,
+            Err(e) => {
+                rid::post(Confirm::DeployError(req_id, e));
+                return;
+            }
+        };
+        let node_id = NodeId(Uuid::parse_str(node_id).unwrap());
+
+        let model = self.state.as_ref().unwrap().model();
+        *model.req_id.lock().unwrap() = req_id;
+
+        if let Err(e) = model.deploy_partial(node_id, with_downstream, &input_values) {
+            rid::post(Confirm::DeployError(req_id, e.to_string()));
+        }
+    }
+
+    fn import(&mut self, req_id: u64, path: &str, passphrase: Option<&str>) {
+        let model = self.state.as_mut().unwrap().model_mut();
+
+        let redacted_node_ids = match model.import(path, passphrase) {
+            Ok(redacted_node_ids) => redacted_node_ids,
+            Err(e) => {
+                rid::post(Confirm::ImportError(req_id, e));
+                return;
+            }
+        };
+
+        let state = self.state.as_mut().unwrap();
+        state.reset();
+        self.refresh_ui();
+        rid::post(Confirm::LoadGraph(req_id, "".to_owned()));
+
+        if !redacted_node_ids.is_empty() {
+            let node_ids: Vec<String> = redacted_node_ids
+                .iter()
+                .map(|node_id| node_id.0.to_string())
+                .collect();
+            rid::post(Confirm::RedactedConsts(
+                req_id,
+                serde_json::to_string(&node_ids).unwrap(),
+            ));
+        }
+    }
+
+    // validation errors are shown before deploying, without refreshing on every keystroke
+    fn refresh_if_const_error_changed(&mut self, node_id: NodeId, req_id: u64) {
+        let model = self.state.as_ref().unwrap().model();
+        let error = model
+            .const_error(&node_id)
+            .map(|e| serde_json::to_string(&e).unwrap())
+            .unwrap_or_default();
+        let view_error = self
+            .view
+            .nodes
+            .get(&node_id.0.to_string())
+            .map(|node| node.error.as_str())
+            .unwrap_or_default();
+
+        if error != view_error {
+            self.refresh_ui();
+            rid::post(Confirm::RefreshUI(req_id, "".to_owned()));
+        }
+    }
+
     fn refresh_selection(&mut self) {
         let state = self.state.as_ref().unwrap();
 
@@ -1422,15 +2135,23 @@ impl Store {
             .map(|(_, model)| model.to.0.to_string())
             .collect();
 
+        let selected_edge_ids: Vec<String> = state
+            .selected_edge_ids()
+            .map(|edge_id| edge_id.0.to_string())
+            .collect();
+
         let is_selected_node_ids_changed = old_view.selected_node_ids != selected_node_ids;
+        let is_selected_edge_ids_changed = old_view.selected_edge_ids != selected_edge_ids;
         let is_selected_command_changed = old_view.selected_command_ids != selected_command_ids;
         let is_command_changed = old_view.command != command;
 
         changes.is_selected_node_ids_changed = is_selected_node_ids_changed;
+        changes.is_selected_edge_ids_changed = is_selected_edge_ids_changed;
         changes.is_command_changed = is_command_changed;
 
         self.view.command = command;
         self.view.selected_node_ids = selected_node_ids;
+        self.view.selected_edge_ids = selected_edge_ids;
         self.view.selected_command_ids = selected_command_ids;
 
         self.last_view_changes = changes;
@@ -1481,53 +2202,18 @@ impl Store {
             let input = state.model().inputs().get(&edge.input_id).unwrap();
             let output = state.model().outputs().get(&edge.output_id).unwrap();
 
+            // orthogonal edges may have to go around the moved nodes
             if !node_ids_to_update.contains(&input.parent_node_id)
                 && !node_ids_to_update.contains(&output.parent_node_id)
+                && edge.routing == EdgeRouting::Direct
             {
                 continue;
             }
 
-            let input_node = state.model().nodes().get(&input.parent_node_id).unwrap();
-            let input_data = match input_node {
-                NodeModel::Widget(data) => data,
-                // NodeModel::Data(_) => panic!(),
-            };
-            let output_node = state.model().nodes().get(&output.parent_node_id).unwrap();
-            let output_data = match output_node {
-                NodeModel::Widget(data) => data,
-                // NodeModel::Data(_) => panic!(),
-            };
-
-            //let node_view = node_views
-            //    .get_mut(&input.parent_node_id.0.to_string())
-            //   .unwrap();
-
-            let (dx1, dy1) = if node_ids_to_update.contains(&output.parent_node_id) {
-                (dx, dy)
-            } else {
-                (0.0, 0.0)
-            };
-
-            let (dx2, dy2) = if node_ids_to_update.contains(&input.parent_node_id) {
-                (dx, dy)
-            } else {
-                (0.0, 0.0)
-            };
-
             changes.changed_flow_edges_ids.push(edge_id.0.to_string());
             self.view.flow_edges.insert(
                 edge_id.0.to_string(),
-                EdgeView {
-                    from: edge.output_id.0.to_string(), // FIXME: input.label.clone(),
-                    to: edge.input_id.0.to_string(),    // FIXME: output.label.clone(),
-                    edge_type: ViewEdgeType::Flow,
-                    from_coords_x: (output_data.coords.x + output.local_coords.x + dx1) as i64 + 35,
-                    from_coords_y: (output_data.coords.y + output.local_coords.y + dy1) as i64
-                        + INPUT_SIZE / 2, //half width of input size
-                    to_coords_x: (input_data.coords.x + input.local_coords.x + dx2) as i64 + 15,
-                    to_coords_y: (input_data.coords.y + input.local_coords.y + dy2) as i64
-                        + INPUT_SIZE / 2,
-                },
+                Self::flow_edge_view(state, *edge_id, edge, &node_ids_to_update, dx, dy),
             );
         }
 
@@ -1541,6 +2227,13 @@ impl Store {
 
         let state = self.state.as_ref().unwrap();
 
+        // edge being rewired is replaced by the dragged edge
+        let rewired_edge_id = if let UiState::EdgeRewire(edge_id, _, _) = state.ui_state {
+            Some(edge_id)
+        } else {
+            None
+        };
+
         // SELECTION
         let selection = if let UiState::Selection(start_coords, coords) = &state.ui_state {
             Selection {
@@ -1619,6 +2312,9 @@ impl Store {
                                 RunState::Success(_) => RunStateView::Success,
                                 RunState::Canceled => RunStateView::Canceled,
                             },
+                            None if state.model().paused_node_ids().contains(node_id) => {
+                                RunStateView::Paused
+                            }
                             None => RunStateView::NotRunning,
                         },
                         elapsed_time: match state.model().run_status.get(node_id) {
@@ -1634,7 +2330,12 @@ impl Store {
                                 RunState::Failed(_, e) => e.clone(),
                                 _ => String::new(),
                             },
-                            None => String::new(),
+                            // { kind, message } for invalid const values
+                            None => state
+                                .model()
+                                .const_error(node_id)
+                                .map(|e| serde_json::to_string(&e).unwrap())
+                                .unwrap_or_default(),
                         },
                         print_output: match state.model().run_status.get(node_id) {
                             Some(v) => match v.value().1 {
@@ -1650,6 +2351,29 @@ impl Store {
                         passthrough: bool::default(),
                         default_value: String::new(),
                         has_default: bool::default(),
+                        literal_value: String::new(),
+                        has_literal: false,
+                        is_secret: state.model().secret_node_ids.contains(node_id),
+                        network_values: state
+                            .model()
+                            .network_values
+                            .get(node_id)
+                            .map(|values| serde_json::to_string(values).unwrap())
+                            .unwrap_or_default(),
+                        is_missing_network_value: state.model().is_missing_network_value(node_id),
+                        is_response: state.model().response_node_id == Some(*node_id),
+                        is_breakpoint: state.model().breakpoints.contains(node_id),
+                        input_values: state
+                            .model()
+                            .paused_input_values(*node_id)
+                            .map(|input_values| serde_json::to_string(&input_values).unwrap())
+                            .unwrap_or_default(),
+                        output_values: state
+                            .model()
+                            .output_values(*node_id)
+                            .map(|output_values| serde_json::to_string(&output_values).unwrap())
+                            .unwrap_or_default(),
+                        port_value: String::new(),
                     },
                 )
             });
@@ -1681,7 +2405,9 @@ impl Store {
                         .model()
                         .flow_edges()
                         .iter()
-                        .filter(|(_, edge)| &edge.input_id == input_id)
+                        .filter(|(edge_id, edge)| {
+                            &edge.input_id == input_id && Some(**edge_id) != rewired_edge_id
+                        })
                         .map(
                             |(edge_id, _flow_edge_model)| {
                                 edge_id.0.to_string()
@@ -1700,6 +2426,20 @@ impl Store {
                     passthrough: bool::default(),
                     default_value: input.default_value.to_owned(),
                     has_default: input.has_default.to_owned(),
+                    literal_value: input.literal.clone().unwrap_or_default(),
+                    has_literal: input.literal.is_some(),
+                    is_secret: false,
+                    network_values: String::new(),
+                    is_missing_network_value: false,
+                    is_response: false,
+                    is_breakpoint: false,
+                    input_values: String::new(),
+                    output_values: String::new(),
+                    port_value: state
+                        .model()
+                        .input_value(*input_id)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default(),
                 },
             )
         });
@@ -1729,7 +2469,9 @@ impl Store {
                         .model()
                         .flow_edges()
                         .iter()
-                        .filter(|(_, edge)| &edge.output_id == output_id)
+                        .filter(|(edge_id, edge)| {
+                            &edge.output_id == output_id && Some(**edge_id) != rewired_edge_id
+                        })
                         .map(
                             |(edge_id, flow_edge_model)| edge_id.0.to_string(), //flow_edge_model.output_id.0.to_string(), // FIXME: should be the edge id, not the input id
                         )
@@ -1746,6 +2488,20 @@ impl Store {
                     passthrough: output.passthrough,
                     default_value: String::default(),
                     has_default: bool::default(),
+                    literal_value: String::new(),
+                    has_literal: false,
+                    is_secret: false,
+                    network_values: String::new(),
+                    is_missing_network_value: false,
+                    is_response: false,
+                    is_breakpoint: false,
+                    input_values: String::new(),
+                    output_values: String::new(),
+                    port_value: state
+                        .model()
+                        .output_value(*output_id)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default(),
                 },
             )
         });
@@ -1782,6 +2538,8 @@ impl Store {
                     Some(coords) => coords.y as i64,
                     None => 0,
                 },
+                points: vec![],
+                label: String::new(),
             };
 
             // TODO: always add for widget nodes
@@ -1812,50 +2570,13 @@ impl Store {
         let mut flow_edges = HashMap::new();
         // insert input/output edges
         for (edge_id, edge) in state.model().flow_edges() {
-            let input = state.model().inputs().get(&edge.input_id).unwrap();
-            let output = state.model().outputs().get(&edge.output_id).unwrap();
-
-            let input_node = state.model().nodes().get(&input.parent_node_id).unwrap();
-            let input_data = match input_node {
-                NodeModel::Widget(data) => data,
-                // NodeModel::Data(_) => panic!(),
-            };
-            let output_node = state.model().nodes().get(&output.parent_node_id).unwrap();
-            let output_data = match output_node {
-                NodeModel::Widget(data) => data,
-                // NodeModel::Data(_) => panic!(),
-            };
-
-            //let node_view = node_views
-            //    .get_mut(&input.parent_node_id.0.to_string())
-            //   .unwrap();
-
-            let (dx1, dy1) = if node_ids.contains(&output.parent_node_id) {
-                (dx, dy)
-            } else {
-                (0.0, 0.0)
-            };
-
-            let (dx2, dy2) = if node_ids.contains(&input.parent_node_id) {
-                (dx, dy)
-            } else {
-                (0.0, 0.0)
-            };
-
+            if Some(*edge_id) == rewired_edge_id {
+                continue;
+            }
             // node_view.outbound_edges
             flow_edges.insert(
                 edge_id.0.to_string(),
-                EdgeView {
-                    from: edge.output_id.0.to_string(), // FIXME: input.label.clone(),
-                    to: edge.input_id.0.to_string(),    // FIXME: output.label.clone(),
-                    edge_type: ViewEdgeType::Flow,
-                    from_coords_x: (output_data.coords.x + output.local_coords.x + dx1) as i64 + 35,
-                    from_coords_y: (output_data.coords.y + output.local_coords.y + dy1) as i64
-                        + INPUT_SIZE / 2, //half width of input size
-                    to_coords_x: (input_data.coords.x + input.local_coords.x + dx2) as i64 + 15,
-                    to_coords_y: (input_data.coords.y + input.local_coords.y + dy2) as i64
-                        + INPUT_SIZE / 2,
-                },
+                Self::flow_edge_view(state, *edge_id, edge, &node_ids, dx, dy),
             );
         }
 
@@ -1869,6 +2590,11 @@ impl Store {
             .selected_node_ids()
             .map(|uuid| uuid.0.to_string())
             .collect();
+
+        let selected_edge_ids: Vec<String> = state
+            .selected_edge_ids()
+            .map(|edge_id| edge_id.0.to_string())
+            .collect();
         // dbg!(selected_node_ids.clone());
 
         //child id, not really command
@@ -1886,7 +2612,13 @@ impl Store {
 
         // Add currently creatable edge
         //
-        if let UiState::Edge(input_id, coords) = state.ui_state {
+        let dragged_edge = match state.ui_state {
+            UiState::Edge(port_id, coords) | UiState::EdgeRewire(_, port_id, coords) => {
+                Some((port_id, coords))
+            }
+            _ => None,
+        };
+        if let Some((input_id, coords)) = dragged_edge {
             const DUMMY_EDGE_ID: &'static str = "dummy_edge";
             const DUMMY_NODE_ID: &'static str = "dummy_node";
 
@@ -1912,12 +2644,24 @@ impl Store {
                             from: DUMMY_NODE_ID.to_owned(),
                             to: input_id.0.to_string(), //node_id.0.to_string(),
                             edge_type: ViewEdgeType::Flow,
+                            label: String::new(),
                             // +15 +25 is adjustment for offset port and edge in flutter dragging
                             from_coords_x: coords.x as i64,
                             from_coords_y: coords.y as i64,
                             to_coords_x: (input_data.coords.x + input.local_coords.x) as i64 + 15,
                             to_coords_y: (input_data.coords.y + input.local_coords.y) as i64
                                 + INPUT_SIZE / 2,
+                            points: vec![
+                                EdgePoint {
+                                    x: coords.x as i64,
+                                    y: coords.y as i64,
+                                },
+                                EdgePoint {
+                                    x: (input_data.coords.x + input.local_coords.x) as i64 + 15,
+                                    y: (input_data.coords.y + input.local_coords.y) as i64
+                                        + INPUT_SIZE / 2,
+                                },
+                            ],
                         },
                     );
 
@@ -1954,6 +2698,16 @@ impl Store {
                             passthrough: bool::default(),
                             default_value: String::new(),
                             has_default: bool::default(),
+                            literal_value: String::new(),
+                            has_literal: false,
+                            is_secret: false,
+                            network_values: String::new(),
+                            is_missing_network_value: false,
+                            is_response: false,
+                            is_breakpoint: false,
+                            input_values: String::new(),
+                            output_values: String::new(),
+                            port_value: String::new(),
                         },
                     );
 
@@ -2068,6 +2822,16 @@ impl Store {
                             passthrough: bool::default(),
                             default_value: String::new(),
                             has_default: bool::default(),
+                            literal_value: String::new(),
+                            has_literal: false,
+                            is_secret: false,
+                            network_values: String::new(),
+                            is_missing_network_value: false,
+                            is_response: false,
+                            is_breakpoint: false,
+                            input_values: String::new(),
+                            output_values: String::new(),
+                            port_value: String::new(),
                         },
                     );
 
@@ -2077,6 +2841,7 @@ impl Store {
                             to: DUMMY_NODE_ID.to_owned(),  // FIXME: "".to_owned(),
                             from: output_id.0.to_string(), // FIXME: output.label.clone(),
                             edge_type: ViewEdgeType::Flow,
+                            label: String::new(),
                             from_coords_x: (output_data.coords.x + output.local_coords.x) as i64
                                 + 35,
                             from_coords_y: (output_data.coords.y + output.local_coords.y) as i64
@@ -2084,6 +2849,16 @@ impl Store {
                             // +35 +25 is adjustment for offset port and edge in flutter dragging
                             to_coords_x: coords.x as i64,
                             to_coords_y: coords.y as i64,
+                            points: vec![
+                                EdgePoint {
+                                    x: (output_data.coords.x + output.local_coords.x) as i64 + 35,
+                                    y: (output_data.coords.y + output.local_coords.y) as i64 + 25,
+                                },
+                                EdgePoint {
+                                    x: coords.x as i64,
+                                    y: coords.y as i64,
+                                },
+                            ],
                         },
                     );
 
@@ -2204,6 +2979,7 @@ impl Store {
             nodes,
             flow_edges,
             selected_node_ids,
+            selected_edge_ids,
             selected_command_ids,
             selection,
             command,
@@ -2214,11 +2990,89 @@ impl Store {
             transform_screenshot,
             bookmarks,
             solana_net: state.model().solana_net,
+            solana_endpoint: state
+                .model()
+                .solana_endpoint
+                .as_ref()
+                .map(|endpoint| SolanaEndpointView {
+                    rpc_url: endpoint.rpc_url.clone(),
+                    ws_url: endpoint.ws_url.clone(),
+                })
+                .unwrap_or_default(),
             ui_state_debug: DebugData {
                 ui_state: serde_json::to_string(&state.ui_state).unwrap(),
                 mapping_kind: serde_json::to_string(&state.mapping_kind).unwrap(),
                 selected_node_ids: serde_json::to_string(&state.selected_node_ids).unwrap(),
             },
+            vault: VaultView {
+                is_unlocked: state.model().vault.lock().unwrap().is_unlocked(),
+                entries: state
+                    .model()
+                    .vault
+                    .lock()
+                    .unwrap()
+                    .entries()
+                    .map(|(vault_id, entry)| VaultEntryView {
+                        id: vault_id.0.to_string(),
+                        label: entry.label.clone(),
+                        kind: entry.kind.clone(),
+                    })
+                    .collect(),
+            },
+            address_book: state
+                .model()
+                .address_book
+                .entries()
+                .map(|entry| AddressEntryView {
+                    name: entry.name.clone(),
+                    pubkey: entry.pubkey.clone(),
+                    networks: serde_json::to_string(&entry.networks).unwrap(),
+                    notes: entry.notes.clone(),
+                })
+                .collect(),
+            wallet: state
+                .model()
+                .wallet
+                .iter()
+                .map(|keypair| WalletKeypairView {
+                    node_id: keypair.node_id.clone(),
+                    label: keypair.label.clone(),
+                    pubkey: keypair.pubkey.clone(),
+                    is_default_payer: keypair.is_default_payer,
+                })
+                .collect(),
+            active_runs: state
+                .model()
+                .active_runs()
+                .into_iter()
+                .map(|(entry, run)| ActiveRunView {
+                    run_id: run.run_id.to_string(),
+                    graph_id: entry.id,
+                    graph_name: entry.name,
+                    network: run.network,
+                    state: run.state,
+                })
+                .collect(),
+            flow_inputs: state
+                .model()
+                .flow_input_list()
+                .into_iter()
+                .map(|input| FlowInputView {
+                    node_id: input.node_id,
+                    name: input.name,
+                    kind: input.kind,
+                    default_value: input
+                        .default
+                        .map(|value| value.to_string())
+                        .unwrap_or_default(),
+                })
+                .collect(),
+            watch_trigger: state
+                .model()
+                .watch_trigger
+                .as_ref()
+                .map(|config| serde_json::to_string(config).unwrap())
+                .unwrap_or_default(),
         };
 
         let node_ids: HashSet<_> = old_view.nodes.keys().chain(new_view.nodes.keys()).collect();
@@ -2274,6 +3128,7 @@ impl Store {
             .collect();
 
         let is_selected_node_ids_changed = old_view.selected_node_ids != new_view.selected_node_ids;
+        let is_selected_edge_ids_changed = old_view.selected_edge_ids != new_view.selected_edge_ids;
         let is_selection_changed = old_view.selection != new_view.selection;
         let is_command_changed = old_view.command != new_view.command;
         let is_text_commands_changed = old_view.text_commands != new_view.text_commands;
@@ -2282,13 +3137,21 @@ impl Store {
         let is_transform_changed = old_view.transform != new_view.transform;
         let is_graph_changed = old_view.graph_entry != new_view.graph_entry;
         let is_bookmark_changed = old_view.bookmarks != new_view.bookmarks;
+        let is_vault_changed = old_view.vault != new_view.vault;
+        let is_address_book_changed = old_view.address_book != new_view.address_book;
+        let is_wallet_changed = old_view.wallet != new_view.wallet;
+        let is_active_runs_changed = old_view.active_runs != new_view.active_runs;
+        let is_flow_inputs_changed = old_view.flow_inputs != new_view.flow_inputs;
+        let is_watch_trigger_changed = old_view.watch_trigger != new_view.watch_trigger;
         let is_transform_screenshot_changed =
             old_view.transform_screenshot != new_view.transform_screenshot;
 
         let changes = LastViewChanges {
             changed_nodes_ids,
             changed_flow_edges_ids,
+            replaced_flow_edges_ids: vec![],
             is_selected_node_ids_changed,
+            is_selected_edge_ids_changed,
             is_selection_changed,
             is_command_changed,
             is_text_commands_changed,
@@ -2298,6 +3161,12 @@ impl Store {
             is_transform_screenshot_changed,
             is_graph_changed,
             is_bookmark_changed,
+            is_vault_changed,
+            is_address_book_changed,
+            is_wallet_changed,
+            is_active_runs_changed,
+            is_flow_inputs_changed,
+            is_watch_trigger_changed,
         };
         self.view = new_view;
         self.last_view_changes = changes;
@@ -2317,7 +3186,9 @@ impl Store {
         self.last_view_changes = LastViewChanges {
             changed_nodes_ids: HashMap::new(),
             changed_flow_edges_ids: vec![],
+            replaced_flow_edges_ids: vec![],
             is_selected_node_ids_changed: false,
+            is_selected_edge_ids_changed: false,
             is_selection_changed: false,
             is_command_changed: false,
             is_text_commands_changed: false,
@@ -2327,6 +3198,12 @@ impl Store {
             is_transform_screenshot_changed: false,
             is_graph_changed: false,
             is_bookmark_changed: false, // FIXME
+            is_vault_changed: false,
+            is_address_book_changed: false,
+            is_wallet_changed: false,
+            is_active_runs_changed: false,
+            is_flow_inputs_changed: false,
+            is_watch_trigger_changed: false,
         };
         /*println!(
             "transform {:10} {:10} {:10}",
@@ -2338,7 +3215,13 @@ impl Store {
         let state = self.state.as_ref().unwrap();
         let mut changes = LastViewChanges::default();
 
-        if let UiState::Edge(input_id, coords) = state.ui_state {
+        let dragged_edge = match state.ui_state {
+            UiState::Edge(port_id, coords) | UiState::EdgeRewire(_, port_id, coords) => {
+                Some((port_id, coords))
+            }
+            _ => None,
+        };
+        if let Some((input_id, coords)) = dragged_edge {
             const DUMMY_EDGE_ID: &'static str = "dummy_edge";
             const DUMMY_NODE_ID: &'static str = "dummy_node";
 
@@ -2367,12 +3250,24 @@ impl Store {
                             from: DUMMY_NODE_ID.to_owned(),
                             to: input_id.0.to_string(), //node_id.0.to_string(),
                             edge_type: ViewEdgeType::Flow,
+                            label: String::new(),
                             // +15 +25 is adjustment for offset port and edge in flutter dragging
                             from_coords_x: coords.x as i64,
                             from_coords_y: coords.y as i64,
                             to_coords_x: (input_data.coords.x + input.local_coords.x) as i64 + 15,
                             to_coords_y: (input_data.coords.y + input.local_coords.y) as i64
                                 + INPUT_SIZE / 2,
+                            points: vec![
+                                EdgePoint {
+                                    x: coords.x as i64,
+                                    y: coords.y as i64,
+                                },
+                                EdgePoint {
+                                    x: (input_data.coords.x + input.local_coords.x) as i64 + 15,
+                                    y: (input_data.coords.y + input.local_coords.y) as i64
+                                        + INPUT_SIZE / 2,
+                                },
+                            ],
                         },
                     );
 
@@ -2422,6 +3317,16 @@ impl Store {
                             passthrough: bool::default(),
                             default_value: String::new(),
                             has_default: bool::default(),
+                            literal_value: String::new(),
+                            has_literal: false,
+                            is_secret: false,
+                            network_values: String::new(),
+                            is_missing_network_value: false,
+                            is_response: false,
+                            is_breakpoint: false,
+                            input_values: String::new(),
+                            output_values: String::new(),
+                            port_value: String::new(),
                         },
                     );
 
@@ -2550,6 +3455,16 @@ impl Store {
                             passthrough: bool::default(),
                             default_value: String::new(),
                             has_default: bool::default(),
+                            literal_value: String::new(),
+                            has_literal: false,
+                            is_secret: false,
+                            network_values: String::new(),
+                            is_missing_network_value: false,
+                            is_response: false,
+                            is_breakpoint: false,
+                            input_values: String::new(),
+                            output_values: String::new(),
+                            port_value: String::new(),
                         },
                     );
 
@@ -2562,6 +3477,7 @@ impl Store {
                             to: DUMMY_NODE_ID.to_owned(),  // FIXME: "".to_owned(),
                             from: output_id.0.to_string(), // FIXME: output.label.clone(),
                             edge_type: ViewEdgeType::Flow,
+                            label: String::new(),
                             from_coords_x: (output_data.coords.x + output.local_coords.x) as i64
                                 + 35,
                             from_coords_y: (output_data.coords.y + output.local_coords.y) as i64
@@ -2569,6 +3485,16 @@ impl Store {
                             // +35 +25 is adjustment for offset port and edge in flutter dragging
                             to_coords_x: coords.x as i64,
                             to_coords_y: coords.y as i64,
+                            points: vec![
+                                EdgePoint {
+                                    x: (output_data.coords.x + output.local_coords.x) as i64 + 35,
+                                    y: (output_data.coords.y + output.local_coords.y) as i64 + 25,
+                                },
+                                EdgePoint {
+                                    x: coords.x as i64,
+                                    y: coords.y as i64,
+                                },
+                            ],
                         },
                     );
 
@@ -2654,7 +3580,9 @@ impl Store {
         self.last_view_changes = LastViewChanges {
             changed_nodes_ids: HashMap::new(),
             changed_flow_edges_ids: vec![],
+            replaced_flow_edges_ids: vec![],
             is_selected_node_ids_changed: false,
+            is_selected_edge_ids_changed: false,
             is_selection_changed: false,
             is_command_changed: false,
             is_text_commands_changed: false,
@@ -2664,6 +3592,12 @@ impl Store {
             is_transform_screenshot_changed: true,
             is_graph_changed: false,
             is_bookmark_changed: false, // FIXME
+            is_vault_changed: false,
+            is_address_book_changed: false,
+            is_wallet_changed: false,
+            is_active_runs_changed: false,
+            is_flow_inputs_changed: false,
+            is_watch_trigger_changed: false,
         };
         /*println!(
             "transform {:10} {:10} {:10}",
@@ -2671,6 +3605,44 @@ impl Store {
         );*/
     }
 
+    // nodes in moved_node_ids are drawn shifted by dx, dy while being dragged
+    fn flow_edge_view(
+        state: &State,
+        edge_id: model::EdgeId,
+        edge: &FlowEdgeModel,
+        moved_node_ids: &HashSet<NodeId>,
+        dx: f64,
+        dy: f64,
+    ) -> EdgeView {
+        let points = state
+            .model()
+            .flow_edge_polyline(edge_id, edge, moved_node_ids, dx, dy);
+        let from = *points.first().unwrap();
+        let to = *points.last().unwrap();
+
+        EdgeView {
+            from: edge.output_id.0.to_string(), // FIXME: input.label.clone(),
+            to: edge.input_id.0.to_string(),    // FIXME: output.label.clone(),
+            edge_type: ViewEdgeType::Flow,
+            label: state
+                .model()
+                .output_value(edge.output_id)
+                .map(|value| view::value_label(&value))
+                .unwrap_or_default(),
+            from_coords_x: from.x,
+            from_coords_y: from.y,
+            to_coords_x: to.x,
+            to_coords_y: to.y,
+            points: points
+                .into_iter()
+                .map(|point| EdgePoint {
+                    x: point.x,
+                    y: point.y,
+                })
+                .collect(),
+        }
+    }
+
     fn get_view_tranform(state: &State) -> crate::view::Camera {
         crate::view::Camera {
             x: Ratio::from(state.transform.x),
@@ -2687,3 +3659,26 @@ impl Store {
         }
     }
 }
+
+fn parse_seed_config(config: &str) -> SeedConfig {
+    if config.is_empty() {
+        SeedConfig::default()
+    } else {
+        serde_json::from_str(config).unwrap()
+    }
+}
+
+// flow input values of Msg::Deploy, by input name
+fn parse_input_values(
+    input_values: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    if input_values.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+
+    match serde_json::from_str(input_values) {
+        Ok(serde_json::Value::Object(input_values)) => Ok(input_values),
+        Ok(_) => Err("flow inputs must be a json object".to_owned()),
+        Err(e) => Err(format!("invalid flow inputs: {}", e)),
+    }
+}
@@ -1,4 +1,5 @@
 use std::borrow::BorrowMut;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
@@ -31,15 +32,40 @@ use uuid::Uuid;
 
 use std::str::FromStr;
 
+use crate::address_book::{AddressBook, AddressEntry, ADDRESS_BOOK_ENTRIES, ADDRESS_BOOK_MARKER};
+use crate::audit::AuditLog;
 use crate::command::commands_map;
 use crate::command::TypeBound;
 use crate::command::INPUT_SIZE;
-use crate::flow_context::FlowContext;
+use crate::command::{is_secret_literal, literal_matches_type_bounds};
+use crate::cost::{estimate, CostEstimate, FeeTable};
+use crate::deploy_check::flow_input_texts;
+use crate::flow_context::{ActiveRun, FlowContext, RunRequest};
+use crate::flow_graph::FlowGraph;
+use crate::flow_input::{self, FlowInput, FLOW_INPUT_MARKER};
+use crate::http_trigger::{HttpTriggerConfig, RESPONSE_MARKER};
+use crate::partial_run::{self, DebugSession, PartialRunPlan, PortEdge, BREAKPOINT_MARKER};
+use crate::review::{deploy_review, DeployReview};
+use crate::routing;
+use crate::routing::Point;
+use crate::run_outputs;
+use crate::seed::{self, SeedConfig};
+use crate::watch_trigger::{WatchTriggerConfig, WATCH_TRIGGER_COMMAND, WATCH_TRIGGER_MARKER};
 
 //use crate::model_ext::WidgetType;
 
 use crate::event::Coords;
 use crate::utils::Rect;
+use crate::validation::{
+    const_text_payload, is_address_kind, is_redacted_literal, is_redacted_text, is_secret_kind,
+    validate_const, ValidationError,
+};
+use crate::vault::{parse_reference, EncryptedFile, Vault, VaultError};
+use crate::view::RunStateView;
+use crate::wallet::{
+    find_keypair, keypair_pubkey, parse_cli_keypair, to_cli_keypair, WalletKeypair, WalletSchema,
+    DEFAULT_PAYER_MARKER, WALLET_LABEL_MARKER,
+};
 
 pub const COORDS_MARKER: &str = "COORDS_MARKER";
 pub const DIMENSIONS_MARKER: &str = "DIMENSIONS_MARKER";
@@ -53,8 +79,18 @@ pub const ADDITIONAL_DATA_MARKER: &str = "ADDITIONAL_DATA_MARKER";
 pub const REQ_ID: &str = "REQ_ID";
 pub const BOOKMARKS: &str = "BOOKMARKS";
 pub const BOOKMARK_NAME: &str = "BOOKMARK_NAME";
+pub const WAYPOINTS_MARKER: &str = "WAYPOINTS_MARKER";
+pub const EDGE_ROUTING_MARKER: &str = "EDGE_ROUTING_MARKER";
+pub const INPUT_LITERALS_MARKER: &str = "INPUT_LITERALS_MARKER"; // { input label: value }
+pub const LITERAL_NODE_MARKER: &str = "LITERAL_NODE_MARKER"; // const older versions created in the graph for a literal
+pub const SECRET_MARKER: &str = "SECRET_MARKER"; // const redacted on export, e.g. an auth token
+pub const SOLANA_ENDPOINT_MARKER: &str = "SOLANA_ENDPOINT_MARKER"; // endpoint of localnet/custom on the context node
+pub const SPENDING_CAP_MARKER: &str = "SPENDING_CAP_MARKER"; // max SOL a run can transfer, on the graph node
+pub const NETWORK_VALUES_MARKER: &str = "NETWORK_VALUES_MARKER"; // { "mainnet": const text, ... }
+pub const REDACTED_PLACEHOLDER: &str = "<redacted>";
 
 pub const INPUT_OFFSET: i64 = 50;
+pub const EDGE_HIT_DISTANCE: f64 = 8.0; // how far from a flow edge a click still selects it
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub struct GraphId(pub Uuid);
@@ -93,6 +129,9 @@ pub enum PortId {
 #[derive(Clone)]
 pub struct Db(Arc<DB>);
 
+// ends, waypoints and blocks an orthogonal route was computed for
+type RouteKey = (Point, Point, Vec<Point>, Vec<Rect>);
+
 #[derive(Debug)]
 pub struct Model {
     db: Db, // indraDB implementation
@@ -104,6 +143,7 @@ pub struct Model {
     node_edges: HashMap<NodeEdgeId, NodeEdgeModel>,
 
     flow_edges: HashMap<EdgeId, FlowEdgeModel>,
+    routes: Mutex<HashMap<EdgeId, (RouteKey, Vec<Point>)>>, // orthogonal polylines of flow edges
 
     inputs: HashMap<InputId, InputModel>,
     outputs: HashMap<OutputId, OutputModel>,
@@ -114,11 +154,97 @@ pub struct Model {
     //
     flow_context: FlowContext,
 
-    pub run_status: Arc<DashMap<NodeId, (RunState, Option<String>)>>,
+    // state, print output, output values by port
+    pub run_status: Arc<DashMap<NodeId, (RunState, Option<String>, Properties)>>,
 
     pub req_id: Arc<Mutex<u64>>, //workaround for UI to display command runtime status/errors
 
     pub solana_net: SolanaNet,
+    pub solana_endpoint: Option<SolanaEndpoint>, // None for public clusters
+    pub spending_cap: Option<f64>,               // SOL, opt-in per graph
+    pub watch_trigger: Option<WatchTriggerConfig>, // directory running the current graph
+    pub pending_review: Option<DeployReview>,    // mainnet deploy waiting for confirmation
+    pub pending_input_values: Properties,        // flow inputs of the pending deploy
+
+    pub vault: Arc<Mutex<Vault>>, // secrets referenced by const nodes, shared with the runs
+    pub vault_error: Option<String>, // the vault file couldn't be opened, see take_vault_error
+    pub secret_node_ids: HashSet<NodeId>, // consts marked as secret
+    pub network_values: HashMap<NodeId, BTreeMap<String, String>>, // per network const text
+    pub flow_inputs: HashMap<NodeId, String>, // parameter name of consts given at deploy time
+    pub response_node_id: Option<NodeId>, // print node answering http run_sync requests
+    pub breakpoints: HashSet<NodeId>, // command nodes a deploy pauses before
+    debug_sessions: HashMap<GraphId, DebugSession>, // deploys paused at breakpoints, by graph
+
+    pub address_book: AddressBook, // shared by all graphs
+    address_book_id: GraphId,
+
+    pub audit_log: Arc<Mutex<AuditLog>>, // transactions of all runs, written by the flow context
+
+    pub fee_table: FeeTable,
+    fee_table_path: std::path::PathBuf,
+
+    pub http_trigger: HttpTriggerConfig,
+    http_trigger_path: std::path::PathBuf,
+
+    wallet_graph_id: Option<GraphId>, // from the context node
+    pub wallet: Vec<WalletKeypair>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub redact_secrets: bool,
+    #[serde(default)]
+    pub passphrase: Option<String>, // encrypts the whole file
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeployError {
+    Vault(VaultError),
+    Redacted(Vec<NodeId>),            // consts imported without their value
+    Address(String),                  // unknown address book entry
+    MissingNetworkValue(Vec<NodeId>), // consts without a value for the selected network
+    SpendingCap(String),
+    FlowInput(String), // unknown, missing or invalid deploy parameter
+    MissingCachedOutput(Vec<NodeId>), // upstream nodes of a partial run without outputs
+    PartialRun(String),
+    Endpoint(SolanaNet), // localnet or custom, the runner only connects to public clusters
+    Runner(String),      // the copy of the graph couldn't be deployed
+}
+
+impl fmt::Display for DeployError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployError::Vault(e) => write!(f, "{}", e),
+            DeployError::Redacted(node_ids) => {
+                write!(f, "{} redacted values need to be entered", node_ids.len())
+            }
+            DeployError::Address(e) => write!(f, "{}", e),
+            DeployError::MissingNetworkValue(node_ids) => {
+                write!(f, "{} values are missing for this network", node_ids.len())
+            }
+            DeployError::SpendingCap(e) => write!(f, "{}", e),
+            DeployError::FlowInput(e) => write!(f, "{}", e),
+            DeployError::MissingCachedOutput(node_ids) => write!(
+                f,
+                "{} upstream nodes have no outputs yet, run the whole flow first",
+                node_ids.len()
+            ),
+            DeployError::PartialRun(e) => write!(f, "{}", e),
+            DeployError::Endpoint(solana_net) => write!(
+                f,
+                "flows can't be run on {} yet, switch to devnet, testnet or mainnet",
+                solana_net.name()
+            ),
+            DeployError::Runner(e) => write!(f, "flow couldn't be deployed: {}", e),
+        }
+    }
+}
+
+impl From<VaultError> for DeployError {
+    fn from(e: VaultError) -> Self {
+        DeployError::Vault(e)
+    }
 }
 
 impl fmt::Debug for Db {
@@ -257,6 +383,22 @@ pub struct FlowEdgeModel {
     pub output_id: OutputId,
     pub edge_type: EdgeType,
     pub db_edge_id: EdgeId,
+    pub waypoints: Vec<Coords>, // user placed, in canvas coords
+    pub routing: EdgeRouting,
+}
+
+/// How the polyline between two ports is built
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeRouting {
+    Direct,     // straight segments through the waypoints
+    Orthogonal, // horizontal/vertical segments going around blocks
+}
+
+impl Default for EdgeRouting {
+    fn default() -> Self {
+        EdgeRouting::Direct
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -271,6 +413,7 @@ pub struct InputModel {
     pub type_bounds: String,
     pub has_default: bool,
     pub default_value: String,
+    pub literal: Option<String>, // used when the input is not connected
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -297,6 +440,31 @@ pub enum SolanaNet {
     Devnet,
     Testnet,
     Mainnet,
+    Localnet, // solana-test-validator
+    Custom,   // endpoint in Model::solana_endpoint
+}
+
+/// rpc and websocket urls of a localnet or custom cluster
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SolanaEndpoint {
+    pub rpc_url: String,
+    pub ws_url: String,
+}
+
+impl SolanaEndpoint {
+    pub fn localnet() -> Self {
+        Self {
+            rpc_url: "http://127.0.0.1:8899".to_owned(),
+            ws_url: "ws://127.0.0.1:8900".to_owned(),
+        }
+    }
+}
+
+// stored next to the solana::Config of the context node, which only knows public clusters
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SolanaEndpointConfig {
+    pub solana_net: SolanaNet,
+    pub endpoint: SolanaEndpoint,
 }
 
 impl From<SolanaNet> for BackendSolanaNet {
@@ -305,6 +473,53 @@ impl From<SolanaNet> for BackendSolanaNet {
             SolanaNet::Devnet => BackendSolanaNet::Devnet,
             SolanaNet::Testnet => BackendSolanaNet::Testnet,
             SolanaNet::Mainnet => BackendSolanaNet::Mainnet,
+            // local and custom clusters behave like devnet, e.g. for airdrops
+            SolanaNet::Localnet | SolanaNet::Custom => BackendSolanaNet::Devnet,
+        }
+    }
+}
+
+impl SolanaNet {
+    pub const ALL: [SolanaNet; 5] = [
+        SolanaNet::Devnet,
+        SolanaNet::Testnet,
+        SolanaNet::Mainnet,
+        SolanaNet::Localnet,
+        SolanaNet::Custom,
+    ];
+
+    /// whether the runner signs on mainnet, where SOL is real
+    pub fn is_mainnet(&self) -> bool {
+        matches!(BackendSolanaNet::from(*self), BackendSolanaNet::Mainnet)
+    }
+
+    // network checked against Command::AVAILABILITY
+    pub fn availability_net(&self) -> SolanaNet {
+        match self {
+            SolanaNet::Localnet | SolanaNet::Custom => SolanaNet::Devnet,
+            solana_net => *solana_net,
+        }
+    }
+
+    // key of per network values, e.g. in the address book
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolanaNet::Devnet => "devnet",
+            SolanaNet::Testnet => "testnet",
+            SolanaNet::Mainnet => "mainnet",
+            SolanaNet::Localnet => "localnet",
+            SolanaNet::Custom => "custom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SolanaNet> {
+        match name {
+            "devnet" => Some(SolanaNet::Devnet),
+            "testnet" => Some(SolanaNet::Testnet),
+            "mainnet" => Some(SolanaNet::Mainnet),
+            "localnet" => Some(SolanaNet::Localnet),
+            "custom" => Some(SolanaNet::Custom),
+            _ => None,
         }
     }
 }
@@ -387,12 +602,25 @@ impl Model {
         //     _ =>
         // };
 
-        let graph_list = block_on(db.execute(Action::Query(QueryKind::ListGraphs)))
+        let graph_nodes = block_on(db.execute(Action::Query(QueryKind::ListGraphs)))
             .unwrap()
             .into_node_list()
             .unwrap()
             .into_iter()
             .filter(|(_, properties)| properties.contains_key(FLOW_GRAPH_MARKER))
+            .collect::<Vec<_>>();
+
+        // every graph keeps watching its directory, not only the open one
+        let watch_triggers = graph_nodes
+            .iter()
+            .filter_map(|(node_id, properties)| {
+                let config = properties.get(WATCH_TRIGGER_MARKER)?.clone();
+                Some((GraphId(*node_id), serde_json::from_value(config).ok()?))
+            })
+            .collect::<Vec<(GraphId, WatchTriggerConfig)>>();
+
+        let graph_list = graph_nodes
+            .into_iter()
             .map(|(node_id, properties)| GraphEntry {
                 id: node_id.to_string(),
                 name: properties.get("name").unwrap().as_str().unwrap().to_owned(),
@@ -404,6 +632,44 @@ impl Model {
         let run_status = Arc::new(DashMap::new());
         let req_id = Arc::new(Mutex::new(u64::default()));
 
+        let vault_path = std::path::Path::new(&log_path).join("vault.json");
+        let (vault, vault_error) = match Vault::open(&vault_path) {
+            Ok(vault) => (vault, None),
+            // kept for recovery, a new vault is created on the next unlock
+            Err(e) => match std::fs::rename(&vault_path, vault_path.with_extension("json.corrupt"))
+            {
+                Ok(()) => (
+                    Vault::unreadable(&vault_path),
+                    Some(format!("{}, moved to vault.json.corrupt", e)),
+                ),
+                Err(rename_error) => (
+                    Vault::unreadable(&vault_path),
+                    Some(format!(
+                        "{}, it couldn't be moved to vault.json.corrupt: {}",
+                        e, rename_error
+                    )),
+                ),
+            },
+        };
+        if let Some(e) = &vault_error {
+            eprintln!("{}", e);
+        }
+        let vault = Arc::new(Mutex::new(vault));
+        let (address_book_id, address_book) = load_address_book(Db(db.clone()));
+        let audit_log = Arc::new(Mutex::new(AuditLog::open(
+            std::path::Path::new(&log_path).join("audit.jsonl"),
+        )));
+        let fee_table_path = std::path::Path::new(&log_path).join("fee_table.json");
+        let fee_table = FeeTable::open(&fee_table_path).unwrap_or_else(|e| {
+            eprintln!("error while reading fee table, using defaults: {}", e);
+            FeeTable::default()
+        });
+        let http_trigger_path = std::path::Path::new(&log_path).join("http_trigger.json");
+        let http_trigger = HttpTriggerConfig::open(&http_trigger_path).unwrap_or_else(|e| {
+            eprintln!("error while reading http trigger config, disabled: {}", e);
+            HttpTriggerConfig::default()
+        });
+
         let graph_id = Arc::new(Mutex::new(GraphId(graph_id)));
 
         let graph_entry = graph_list
@@ -418,6 +684,7 @@ impl Model {
             context_node_id, // this will be replaced when we call read_graph
             nodes: HashMap::new(),
             flow_edges: HashMap::new(),
+            routes: Mutex::new(HashMap::new()),
             node_edges: HashMap::new(),
             inputs: HashMap::new(),
             outputs: HashMap::new(),
@@ -427,19 +694,55 @@ impl Model {
                 db.clone(),
                 run_status.clone(),
                 req_id.clone(),
-                graph_id.clone(),
                 graph_entry, //TODO wrong, doesn't update
                 log_path,
+                audit_log.clone(),
+                vault.clone(),
+                address_book_id,
             ),
             run_status,
             req_id,
             solana_net: SolanaNet::Devnet,
+            solana_endpoint: None,
+            spending_cap: None,
+            watch_trigger: None,
+            pending_review: None,
+            pending_input_values: Properties::new(),
+            vault,
+            vault_error,
+            secret_node_ids: HashSet::new(),
+            network_values: HashMap::new(),
+            flow_inputs: HashMap::new(),
+            response_node_id: None,
+            breakpoints: HashSet::new(),
+            debug_sessions: HashMap::new(),
+            address_book,
+            address_book_id,
+            audit_log,
+            fee_table,
+            fee_table_path,
+            http_trigger,
+            http_trigger_path,
+            wallet_graph_id: None,
+            wallet: Vec::new(),
         };
 
         model.read_graph(model.graph_id());
 
         assert_ne!(context_node_id, model.context_node_id);
 
+        if model.http_trigger.enabled {
+            block_on(
+                model
+                    .flow_context
+                    .set_http_trigger(model.http_trigger.clone()),
+            );
+        }
+
+        for (graph_id, config) in watch_triggers {
+            block_on(model.flow_context.set_watch_trigger(graph_id, Some(config)));
+        }
+
         model
     }
 
@@ -504,7 +807,8 @@ impl Model {
     // }
 
     // TODO how to pass req_id to refresh UI?
-    pub fn deploy(&self) {
+    /// input_values are the flow parameters by name, missing ones use the canvas value
+    pub fn deploy(&mut self, input_values: &Properties) -> Result<(), DeployError> {
         /*
         let graph = block_on(
             self.db
@@ -518,18 +822,489 @@ impl Model {
         println!("{:#?}", graph);
         */
 
-        self.run_status.clear();
-        block_on(self.flow_context.deploy(self.graph_id()));
+        self.clear_run_status();
+        self.debug_sessions.remove(&self.graph_id());
+
+        // the graph is checked and copied with its literals and secrets by the flow context
+        block_on(self.flow_context.deploy(RunRequest {
+            flow_id: self.graph_id(),
+            input_values: input_values.clone(),
+            trigger_outputs: Properties::new(),
+            unattended: false,
+            session: None,
+            plan: None,
+        }))?;
+
+        Ok(())
+    }
+
+    /// what a deploy with these flow inputs will sign, from the graph as stored in the db
+    pub fn deploy_review(&self, input_values: &Properties) -> Result<DeployReview, DeployError> {
+        let flow = block_on(FlowGraph::read(&*self.db.0, self.graph_id()))
+            .ok_or_else(|| DeployError::Runner("graph not found".to_owned()))?;
+        let input_texts = flow_input_texts(&flow, input_values)?;
+
+        Ok(deploy_review(
+            &flow,
+            &input_texts,
+            &self.vault.lock().unwrap(),
+        ))
+    }
+
+    /// PARTIAL RUN
+    /// runs the node, or the node and the nodes downstream of it, from a copy of the graph
+    /// where inputs connected to other nodes get the outputs of their latest run
+    ///
+    pub fn deploy_partial(
+        &self,
+        node_id: NodeId,
+        with_downstream: bool,
+        input_values: &Properties,
+    ) -> Result<(), DeployError> {
+        match self.nodes.get(&node_id) {
+            Some(NodeModel::Widget(data)) if data.command_name.is_some() => {}
+            _ => {
+                return Err(DeployError::PartialRun(
+                    "only command nodes can be run".to_owned(),
+                ))
+            }
+        }
+
+        // nobody reviews what a partial run signs
+        if self.solana_net.is_mainnet() && self.spending_cap.is_none() {
+            return Err(DeployError::PartialRun(
+                "mainnet nodes need a spending cap to be run alone".to_owned(),
+            ));
+        }
+
+        let plan = partial_run::plan(node_id, with_downstream, &self.port_edges());
+        self.deploy_plan(&plan, input_values, None)
+    }
+
+    // deploys a copy of the nodes of the plan, given the cached outputs of their upstream nodes
+    fn deploy_plan(
+        &self,
+        plan: &PartialRunPlan,
+        input_values: &Properties,
+        session: Option<Uuid>,
+    ) -> Result<(), DeployError> {
+        // upstream nodes keep the state of the run their outputs come from
+        for node_id in plan.node_ids.iter() {
+            self.run_status.remove(node_id);
+        }
+
+        block_on(self.flow_context.deploy(RunRequest {
+            flow_id: self.graph_id(),
+            input_values: input_values.clone(),
+            trigger_outputs: Properties::new(),
+            unattended: false,
+            session,
+            plan: Some(plan.clone()),
+        }))?;
+
+        Ok(())
+    }
+
+    fn port_edges(&self) -> Vec<PortEdge> {
+        self.flow_edges
+            .values()
+            .map(|edge| {
+                let input = self.inputs.get(&edge.input_id).unwrap();
+                let output = self.outputs.get(&edge.output_id).unwrap();
+                PortEdge {
+                    from: output.command_id,
+                    output: output.label.clone(),
+                    to: input.command_id,
+                    input: input.label.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// BREAKPOINTS
+    /// the runner can't pause a run, a deploy with breakpoints runs in segments
+    /// up to the breakpoints, each a partial run given the outputs of the previous ones,
+    /// until resume_run deploys the next segment
+    ///
+    /// segments are runs of one debug session, shown as one run with one run log
+    ///
+    pub fn set_breakpoint(&mut self, node_id: NodeId, is_breakpoint: bool) -> Result<(), String> {
+        match self.nodes.get(&node_id) {
+            Some(NodeModel::Widget(data)) if data.command_name.is_some() => {}
+            _ => return Err("only command nodes can have a breakpoint".to_owned()),
+        }
+
+        if is_breakpoint {
+            self.breakpoints.insert(node_id);
+        } else {
+            self.breakpoints.remove(&node_id);
+        }
+        self.update_node_marker(node_id, BREAKPOINT_MARKER, is_breakpoint);
+
+        Ok(())
+    }
+
+    pub fn start_debug_run(&mut self, input_values: &Properties) -> Result<(), DeployError> {
+        self.clear_run_status();
+
+        let session = DebugSession {
+            session_id: Uuid::new_v4(),
+            input_values: input_values.clone(),
+            ..DebugSession::default()
+        };
+        self.debug_sessions.insert(self.graph_id(), session);
+
+        let result = self.run_segment(false);
+        if result.is_err() {
+            self.debug_sessions.remove(&self.graph_id());
+        }
+        result
+    }
+
+    /// runs to the next breakpoint, or only the nodes the run is paused at when stepping
+    pub fn resume_run(&mut self, step: bool) -> Result<(), DeployError> {
+        let graph_id = self.graph_id();
+        match self.debug_run_state(graph_id) {
+            Some(RunStateView::Paused) => {}
+            Some(RunStateView::Running) => {
+                return Err(DeployError::PartialRun("the run isn't paused".to_owned()))
+            }
+            // over or deployed again since
+            _ => {
+                self.debug_sessions.remove(&graph_id);
+                return Err(DeployError::PartialRun("the run isn't paused".to_owned()));
+            }
+        }
+
+        self.run_segment(step)
+    }
+
+    fn run_segment(&mut self, step: bool) -> Result<(), DeployError> {
+        let graph_id = self.graph_id();
+        let node_ids: HashSet<NodeId> = self
+            .iter_widget_nodes()
+            .filter(|(_, data)| data.command_name.is_some())
+            .map(|(node_id, _)| *node_id)
+            .collect();
+        let edges = self.port_edges();
+
+        let mut session = self.debug_sessions.get(&graph_id).cloned().unwrap();
+        let segment = session.next_segment(&node_ids, &edges, &self.breakpoints, step);
+
+        session.is_empty_segment = segment.is_empty();
+        if !segment.is_empty() {
+            let plan = partial_run::plan_nodes(segment.clone(), &edges);
+            self.deploy_plan(&plan, &session.input_values, Some(session.session_id))?;
+        }
+        session.run_id = self.flow_context.run_id(graph_id);
+
+        session.advance(&segment, &node_ids, &edges);
+        self.debug_sessions.insert(graph_id, session);
+
+        Ok(())
+    }
+
+    /// Paused once a segment succeeded with nodes left to run,
+    /// None when the graph has no debug run or was deployed again since
+    pub fn debug_run_state(&self, graph_id: GraphId) -> Option<RunStateView> {
+        let session = self.debug_sessions.get(&graph_id)?;
+        let run = self.flow_context.run(graph_id);
+        if run.as_ref().map(|run| run.run_id) != session.run_id {
+            return None;
+        }
+
+        let state = match run {
+            Some(run) if !session.is_empty_segment => run.state,
+            _ => RunStateView::Success,
+        };
+
+        match state {
+            RunStateView::Success if !session.paused_at.is_empty() => Some(RunStateView::Paused),
+            state => Some(state),
+        }
+    }
+
+    /// OUTPUT VALUES
+    /// values passed along the flow by the latest run, by output port
+    ///
+    pub fn output_values(&self, node_id: NodeId) -> Option<Properties> {
+        self.run_status
+            .get(&node_id)
+            .map(|entry| entry.value().2.clone())
+            .filter(|outputs| !outputs.is_empty())
+    }
+
+    pub fn output_value(&self, output_id: OutputId) -> Option<JsonValue> {
+        let output = self.outputs.get(&output_id)?;
+        self.run_status
+            .get(&output.command_id)?
+            .value()
+            .2
+            .get(&output.label)
+            .cloned()
+    }
+
+    /// value received through the edge connected to the input
+    pub fn input_value(&self, input_id: InputId) -> Option<JsonValue> {
+        self.flow_edges
+            .values()
+            .find(|edge| edge.input_id == input_id)
+            .and_then(|edge| self.output_value(edge.output_id))
     }
 
-    pub fn undeploy(&self) {
-        self.run_status.clear();
-        block_on(self.flow_context.undeploy(self.graph_id()));
+    /// nodes of the current graph the run is paused at
+    pub fn paused_node_ids(&self) -> Vec<NodeId> {
+        let graph_id = self.graph_id();
+        match self.debug_run_state(graph_id) {
+            Some(RunStateView::Paused) => self.debug_sessions[&graph_id].paused_at.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// values a paused node will run with, by input label, secrets and keypairs left out,
+    /// inputs whose value the previous segments didn't capture are missing
+    pub fn paused_input_values(&self, node_id: NodeId) -> Option<Properties> {
+        if !self.paused_node_ids().contains(&node_id) {
+            return None;
+        }
+        let command_name = self.nodes.get(&node_id)?.data().command_name.clone()?;
+        let redacted = json!({ "String": REDACTED_PLACEHOLDER });
+
+        let mut input_values = Properties::new();
+        for edge in self
+            .port_edges()
+            .into_iter()
+            .filter(|edge| edge.to == node_id)
+        {
+            let value = match self
+                .flow_context
+                .cached_outputs(edge.from)
+                .and_then(|outputs| outputs.get(&edge.output).cloned())
+            {
+                Some(value) => Some(run_outputs::redact(&value)),
+                None => self.const_value(edge.from),
+            };
+            if let Some(value) = value {
+                input_values.insert(edge.input, value);
+            }
+        }
+        for input in self
+            .inputs
+            .values()
+            .filter(|input| input.command_id == node_id)
+        {
+            if input_values.contains_key(&input.label) {
+                continue;
+            }
+            let value = match input
+                .literal
+                .as_ref()
+                .and_then(|literal| serde_json::from_str::<JsonValue>(literal).ok())
+            {
+                Some(value) => value,
+                None => continue,
+            };
+            let value = if is_secret_literal(&command_name, &input.label, &value) {
+                redacted.clone()
+            } else {
+                run_outputs::redact(&value)
+            };
+            input_values.insert(input.label.clone(), value);
+        }
+
+        Some(input_values)
+    }
+
+    // value of a const node on the selected network, e.g. { "String": "..." }
+    fn const_value(&self, node_id: NodeId) -> Option<JsonValue> {
+        let data = self.nodes.get(&node_id)?.data();
+        if data.command_name.as_deref() != Some("const") {
+            return None;
+        }
+        if self.secret_node_ids.contains(&node_id) || is_secret_kind(&data.additional_data) {
+            return Some(json!({ "String": REDACTED_PLACEHOLDER }));
+        }
+
+        let text = self
+            .network_values
+            .get(&node_id)
+            .and_then(|values| values.get(self.solana_net.name()))
+            .unwrap_or(&data.text);
+        let value = serde_json::from_str::<JsonValue>(text)
+            .ok()?
+            .get("Const")?
+            .clone();
+        Some(run_outputs::redact(&value))
+    }
+
+    /// ADDRESS BOOK
+    ///
+    pub fn set_address_entry(&mut self, entry: AddressEntry) -> Result<(), String> {
+        self.address_book.insert(entry)?;
+        self.save_address_book();
+        Ok(())
+    }
+
+    pub fn remove_address_entry(&mut self, name: &str) {
+        if self.address_book.remove(name).is_some() {
+            self.save_address_book();
+        }
+    }
+
+    fn save_address_book(&self) {
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(self.address_book_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        props.insert(
+            ADDRESS_BOOK_ENTRIES.into(),
+            serde_json::to_value(&self.address_book).unwrap(),
+        );
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.address_book_id.0,
+            MutateKind::UpdateNode((self.address_book_id.0, props)),
+        )))
+        .unwrap();
+    }
+
+    /// VAULT ERROR
+    /// why the vault file couldn't be opened, reported on the next vault message,
+    /// kept while the file still can't be read so unlocking doesn't overwrite it
+    ///
+    pub fn take_vault_error(&mut self) -> Option<String> {
+        let e = self.vault_error.take()?;
+        if self.vault.lock().unwrap().reopen().is_err() {
+            self.vault_error = Some(e.clone());
+        }
+        Some(e)
+    }
+
+    /// MOVE CONST TO VAULT
+    /// the text of the const is encrypted and replaced by a reference
+    ///
+    pub fn move_const_to_vault(
+        &mut self,
+        node_id: NodeId,
+        label: String,
+    ) -> Result<(), VaultError> {
+        let data = match self.nodes.get(&node_id).unwrap() {
+            NodeModel::Widget(data) => data,
+        };
+        let kind = data.additional_data.clone();
+        let mut value: JsonValue =
+            serde_json::from_str(&data.text).map_err(|_| VaultError::NotText)?;
+
+        // { "Const": { "String": "..." } }
+        let secret = match value
+            .get_mut("Const")
+            .and_then(JsonValue::as_object_mut)
+            .and_then(|value| value.values_mut().next())
+        {
+            Some(JsonValue::String(secret)) => secret,
+            _ => return Err(VaultError::NotText),
+        };
+
+        let vault_id = self.vault.lock().unwrap().add_secret(label, kind, secret)?;
+        *secret = vault_id.reference();
+
+        let text = value.to_string();
+        self.set_node_text(&node_id, text.clone());
+        self.update_const_in_db(node_id, &text);
+
+        Ok(())
+    }
+
+    /// runs of every graph, by name then latest first
+    pub fn active_runs(&self) -> Vec<(GraphEntry, ActiveRun)> {
+        let mut runs: Vec<(GraphEntry, ActiveRun)> = self
+            .flow_context
+            .active_runs()
+            .into_iter()
+            .filter_map(|mut run| {
+                let entry = self
+                    .graph_list
+                    .iter()
+                    .find(|entry| entry.id == run.flow_id.0.to_string())?;
+                // a debug session follows the latest run of its graph
+                let is_latest = self.flow_context.run_id(run.flow_id) == Some(run.run_id);
+                if let Some(state) = self.debug_run_state(run.flow_id).filter(|_| is_latest) {
+                    run.state = state;
+                }
+                Some((entry.clone(), run))
+            })
+            .collect();
+
+        // the segments of a debug session are one run, shown by its latest segment
+        let sessions = runs.clone();
+        runs.retain(|(_, run)| {
+            let session = match run.session {
+                Some(session) => session,
+                None => return true,
+            };
+            !sessions
+                .iter()
+                .any(|(_, other)| other.session == Some(session) && other.started > run.started)
+        });
+        for (_, run) in runs.iter_mut() {
+            if let Some(session) = run.session {
+                run.run_id = session;
+            }
+        }
+        runs.sort_by(|a, b| {
+            a.0.name
+                .cmp(&b.0.name)
+                .then_with(|| b.1.started.cmp(&a.1.started))
+        });
+        runs
+    }
+
+    // run status holds the nodes of every deployed graph
+    fn clear_run_status(&self) {
+        for (node_id, _) in self.iter_widget_nodes() {
+            self.run_status.remove(node_id);
+        }
+    }
+
+    /// nodes that didn't finish are shown as canceled
+    pub fn cancel_run(&mut self) {
+        block_on(self.flow_context.cancel(self.graph_id()));
+        self.debug_sessions.remove(&self.graph_id());
+
+        for (node_id, data) in self.iter_widget_nodes() {
+            if data.command_name.is_none() {
+                continue;
+            }
+
+            let finished = self.run_status.get(node_id).is_some_and(|entry| {
+                matches!(
+                    entry.value().0,
+                    RunState::Success(_) | RunState::Failed(_, _)
+                )
+            });
+            if !finished {
+                self.run_status
+                    .insert(*node_id, (RunState::Canceled, None, Properties::new()));
+            }
+        }
+    }
+
+    pub fn undeploy(&mut self) {
+        self.clear_run_status();
+        block_on(self.flow_context.cancel(self.graph_id()));
+        self.debug_sessions.remove(&self.graph_id());
 
         // TODO: refresh ui
     }
 
-    pub fn change_solana_net(&mut self, solana_net: SolanaNet) {
+    /// endpoint is required for localnet and custom
+    pub fn change_solana_net(&mut self, solana_net: SolanaNet, endpoint: Option<SolanaEndpoint>) {
         let mut ctx_node = block_on(
             self.db
                 .0
@@ -549,6 +1324,22 @@ impl Model {
             serde_json::to_value(&ctx_cfg).unwrap(),
         );
 
+        match &endpoint {
+            Some(endpoint) => {
+                let endpoint_cfg = SolanaEndpointConfig {
+                    solana_net,
+                    endpoint: endpoint.clone(),
+                };
+                ctx_node.properties.insert(
+                    SOLANA_ENDPOINT_MARKER.to_owned(),
+                    serde_json::to_value(&endpoint_cfg).unwrap(),
+                );
+            }
+            None => {
+                ctx_node.properties.remove(SOLANA_ENDPOINT_MARKER);
+            }
+        }
+
         block_on(self.db.0.execute(Action::Mutate(
             self.graph_id().0,
             MutateKind::UpdateNode((ctx_node.node_id, ctx_node.properties)),
@@ -556,13 +1347,19 @@ impl Model {
         .unwrap();
 
         self.solana_net = solana_net;
+        self.solana_endpoint = endpoint;
         println!("Solana Net changed to {:?}", self.solana_net);
     }
 
-    pub fn export(&self, path: String, filename: String) {
+    pub fn export(
+        &self,
+        path: String,
+        filename: String,
+        options: &ExportOptions,
+    ) -> Result<(), String> {
         // get current graph entry
         //
-        let graph = block_on(
+        let mut graph = block_on(
             self.db
                 .0
                 .execute(Action::Query(QueryKind::ReadGraph(self.graph_id().0))),
@@ -583,16 +1380,65 @@ impl Model {
         // let timestamp = chrono::offset::Utc::now().timestamp_millis();
         // dbg!(path.clone());
         // dbg!(filename.clone());
-        std::fs::write(
-            format!("{}.json", path),
-            serde_json::to_vec(&graph).unwrap(),
-        )
-        .unwrap();
+        if options.redact_secrets {
+            for node in graph.nodes.iter_mut() {
+                let is_secret = self.secret_node_ids.contains(&NodeId(node.node_id))
+                    || node.properties.contains_key(SECRET_MARKER);
+                redact_const(&mut node.properties, is_secret);
+                redact_literals(&mut node.properties);
+            }
+        }
+
+        let mut bytes = serde_json::to_vec(&graph).unwrap();
+
+        if let Some(passphrase) = &options.passphrase {
+            let encrypted =
+                EncryptedFile::encrypt(passphrase, &bytes).map_err(|e| e.to_string())?;
+            bytes = encrypted.to_bytes();
+        }
+
+        std::fs::write(format!("{}.json", path), bytes).map_err(|e| e.to_string())
     }
 
-    pub fn import(&mut self, path: &str) {
-        let bytes = std::fs::read(path).unwrap();
-        let graph: Graph = serde_json::from_slice(&bytes).unwrap();
+    /// IMPORT
+    /// returns the consts that were redacted on export and need a new value
+    ///
+    pub fn import(&mut self, path: &str, passphrase: Option<&str>) -> Result<Vec<NodeId>, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+
+        let graph: Graph = match EncryptedFile::from_bytes(&bytes) {
+            Some(encrypted) => {
+                let passphrase = passphrase.ok_or_else(|| "file is encrypted".to_owned())?;
+                let bytes = encrypted.decrypt(passphrase).map_err(|e| e.to_string())?;
+                serde_json::from_slice(&bytes).map_err(|e| e.to_string())?
+            }
+            None => serde_json::from_slice(&bytes).map_err(|e| e.to_string())?,
+        };
+
+        let redacted_node_ids: Vec<Uuid> = graph
+            .nodes
+            .iter()
+            .filter(|node| {
+                let redacted_const = node
+                    .properties
+                    .get(TEXT_MARKER)
+                    .and_then(JsonValue::as_str)
+                    .map_or(false, is_redacted_text);
+                let redacted_literal = node
+                    .properties
+                    .get(INPUT_LITERALS_MARKER)
+                    .and_then(JsonValue::as_object)
+                    .map_or(false, |literals| {
+                        literals
+                            .values()
+                            .filter_map(JsonValue::as_str)
+                            .any(is_redacted_literal)
+                    });
+
+                redacted_const || redacted_literal
+            })
+            .map(|node| node.node_id)
+            .collect();
 
         let properties = json!({
             "name":&Self::random_name(),
@@ -678,7 +1524,468 @@ impl Model {
             .unwrap();
         }
 
-        self.read_graph(GraphId(graph_id));
+        self.read_graph(GraphId(graph_id));
+
+        Ok(redacted_node_ids
+            .iter()
+            .map(|node_id| NodeId(node_id_map[node_id]))
+            .collect())
+    }
+
+    /// WALLET
+    /// keypairs of the wallet graph created with the flow
+    ///
+    pub fn refresh_wallet(&mut self) {
+        let wallet_graph_id = match self.wallet_graph_id {
+            Some(wallet_graph_id) => wallet_graph_id,
+            None => return,
+        };
+
+        let graph = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadGraph(wallet_graph_id.0))),
+        )
+        .unwrap()
+        .into_graph()
+        .unwrap();
+
+        self.wallet = graph
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                WalletKeypair::from_properties(node.node_id.to_string(), &node.properties)
+            })
+            .collect();
+        self.wallet
+            .sort_by(|a, b| a.label.cmp(&b.label).then(a.pubkey.cmp(&b.pubkey)));
+    }
+
+    /// import a `solana-keygen` json file, returns the pubkey
+    pub fn import_keypair(&mut self, path: &str, label: String) -> Result<String, String> {
+        let wallet_graph_id = self.wallet_graph_id.ok_or("flow has no wallet")?;
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let bytes = parse_cli_keypair(&json)?;
+        let pubkey = keypair_pubkey(&bytes)?;
+
+        if self.wallet.iter().any(|keypair| keypair.pubkey == pubkey) {
+            return Err(format!("{} is already in the wallet", pubkey));
+        }
+
+        // store it like the keypairs already in the wallet
+        let schema = match self.wallet.first() {
+            Some(keypair) => {
+                let node_id = NodeId(Uuid::parse_str(&keypair.node_id).unwrap());
+                WalletSchema::from_properties(&self.wallet_keypair_properties(node_id)?)
+                    .unwrap_or_default()
+            }
+            None => WalletSchema::default(),
+        };
+
+        let mut properties = schema.properties(&bytes);
+        properties.insert(WALLET_LABEL_MARKER.into(), JsonValue::String(label));
+
+        block_on(self.db.0.execute(Action::Mutate(
+            wallet_graph_id.0,
+            MutateKind::CreateNode(properties),
+        )))
+        .unwrap();
+
+        self.refresh_wallet();
+        Ok(pubkey)
+    }
+
+    /// write the keypair as a `solana-keygen` json file
+    pub fn export_keypair(&self, node_id: NodeId, path: &str) -> Result<(), String> {
+        let props = self.wallet_keypair_properties(node_id)?;
+        let (_, bytes) = find_keypair(&props).ok_or("node is not a keypair")?;
+
+        std::fs::write(path, to_cli_keypair(&bytes)).map_err(|e| e.to_string())
+    }
+
+    pub fn set_keypair_label(&mut self, node_id: NodeId, label: String) -> Result<(), String> {
+        let mut props = self.wallet_keypair_properties(node_id)?;
+        props.insert(WALLET_LABEL_MARKER.into(), JsonValue::String(label));
+        self.update_wallet_node(node_id, props);
+
+        self.refresh_wallet();
+        Ok(())
+    }
+
+    /// only one keypair of the wallet is the default payer
+    pub fn set_default_payer(&mut self, node_id: NodeId) -> Result<(), String> {
+        self.wallet_keypair_properties(node_id)?;
+
+        let keypairs: Vec<NodeId> = self
+            .wallet
+            .iter()
+            .map(|keypair| NodeId(Uuid::parse_str(&keypair.node_id).unwrap()))
+            .collect();
+        for keypair_id in keypairs {
+            let mut props = self.wallet_keypair_properties(keypair_id)?;
+            if keypair_id == node_id {
+                props.insert(DEFAULT_PAYER_MARKER.into(), JsonValue::Bool(true));
+            } else if props.remove(DEFAULT_PAYER_MARKER).is_none() {
+                continue;
+            }
+            self.update_wallet_node(keypair_id, props);
+        }
+
+        self.refresh_wallet();
+        Ok(())
+    }
+
+    fn wallet_keypair_properties(&self, node_id: NodeId) -> Result<Properties, String> {
+        if !self
+            .wallet
+            .iter()
+            .any(|keypair| keypair.node_id == node_id.0.to_string())
+        {
+            return Err("keypair not found in the wallet".to_owned());
+        }
+
+        Ok(block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(node_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties)
+    }
+
+    fn update_wallet_node(&self, node_id: NodeId, props: Properties) {
+        block_on(self.db.0.execute(Action::Mutate(
+            self.wallet_graph_id.unwrap().0,
+            MutateKind::UpdateNode((node_id.0, props)),
+        )))
+        .unwrap();
+    }
+
+    /// COST ESTIMATE
+    /// fees and rent of the command nodes, from the fee table
+    ///
+    pub fn estimate_costs(&self) -> CostEstimate {
+        let commands = self.iter_widget_nodes().filter_map(|(node_id, data)| {
+            let command_name = data.command_name.as_deref()?;
+            Some((node_id.0.to_string(), command_name))
+        });
+
+        estimate(commands, &self.fee_table)
+    }
+
+    pub fn set_fee_table(&mut self, fee_table: FeeTable) -> Result<(), String> {
+        std::fs::write(
+            &self.fee_table_path,
+            serde_json::to_vec_pretty(&fee_table).unwrap(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        self.fee_table = fee_table;
+        Ok(())
+    }
+
+    /// HTTP TRIGGER
+    /// local server running flows, see http_trigger.rs
+    ///
+    pub fn set_http_trigger(&mut self, config: HttpTriggerConfig) -> Result<(), String> {
+        config.validate()?;
+
+        std::fs::write(
+            &self.http_trigger_path,
+            serde_json::to_vec_pretty(&config).unwrap(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        block_on(self.flow_context.set_http_trigger(config.clone()));
+        self.http_trigger = config;
+        Ok(())
+    }
+
+    /// one print node per graph gives the response body of run_sync
+    pub fn set_response_node(&mut self, node_id: NodeId, is_response: bool) -> Result<(), String> {
+        match self.nodes.get(&node_id) {
+            Some(NodeModel::Widget(data)) if data.command_name.as_deref() == Some("print") => {}
+            _ => return Err("only print nodes can give the response".to_owned()),
+        }
+
+        let previous = if is_response {
+            self.response_node_id.replace(node_id)
+        } else if self.response_node_id == Some(node_id) {
+            self.response_node_id.take()
+        } else {
+            None
+        };
+
+        if let Some(previous) = previous {
+            self.update_node_marker(previous, RESPONSE_MARKER, false);
+        }
+        if is_response {
+            self.update_node_marker(node_id, RESPONSE_MARKER, true);
+        }
+
+        Ok(())
+    }
+
+    fn update_node_marker(&self, node_id: NodeId, marker: &str, is_set: bool) {
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(node_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        if is_set {
+            props.insert(marker.into(), JsonValue::Bool(true));
+        } else {
+            props.remove(marker);
+        }
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::UpdateNode((node_id.0, props)),
+        )))
+        .unwrap();
+    }
+
+    /// SPENDING CAP
+    /// max SOL a run of the current graph transfers, None removes it
+    /// checked against the estimate before deploying, a run going over it is canceled
+    ///
+    pub fn set_spending_cap(&mut self, spending_cap: Option<f64>) {
+        let graph_id = self.graph_id();
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(graph_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        match spending_cap {
+            Some(spending_cap) => props.insert(SPENDING_CAP_MARKER.into(), json!(spending_cap)),
+            None => props.remove(SPENDING_CAP_MARKER),
+        };
+
+        block_on(self.db.0.execute(Action::Mutate(
+            graph_id.0,
+            MutateKind::UpdateNode((graph_id.0, props)),
+        )))
+        .unwrap();
+
+        self.spending_cap = spending_cap;
+    }
+
+    /// WATCH TRIGGER
+    /// runs the current graph for each new or changed file of a directory, None stops watching
+    ///
+    pub fn set_watch_trigger(&mut self, config: Option<WatchTriggerConfig>) -> Result<(), String> {
+        if let Some(config) = &config {
+            config.validate()?;
+
+            // gives the file to the nodes it's connected to
+            let has_trigger_node = self
+                .iter_widget_nodes()
+                .any(|(_, data)| data.command_name.as_deref() == Some(WATCH_TRIGGER_COMMAND));
+            if !has_trigger_node {
+                return Err("add a watch trigger node to the flow first".to_owned());
+            }
+        }
+
+        let graph_id = self.graph_id();
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(graph_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        match &config {
+            Some(config) => props.insert(WATCH_TRIGGER_MARKER.into(), json!(config)),
+            None => props.remove(WATCH_TRIGGER_MARKER),
+        };
+
+        block_on(self.db.0.execute(Action::Mutate(
+            graph_id.0,
+            MutateKind::UpdateNode((graph_id.0, props)),
+        )))
+        .unwrap();
+
+        block_on(
+            self.flow_context
+                .set_watch_trigger(graph_id, config.clone()),
+        );
+        self.watch_trigger = config;
+        Ok(())
+    }
+
+    /// NETWORK VALUES
+    /// a const with per network values uses the one of `solana_net` on deploy,
+    /// None removes the value of that network
+    ///
+    pub fn set_const_network_value(
+        &mut self,
+        node_id: NodeId,
+        network: &str,
+        text: Option<String>,
+    ) -> Result<(), String> {
+        if SolanaNet::from_name(network).is_none() {
+            return Err(format!("unknown network: {}", network));
+        }
+
+        if let Some(text) = &text {
+            let data = match self.nodes.get(&node_id) {
+                Some(NodeModel::Widget(data)) => data,
+                None => return Err("node not found".to_owned()),
+            };
+            serde_json::from_str::<SimpleCommand>(text).map_err(|e| e.to_string())?;
+            validate_const(&data.additional_data, text).map_err(|e| e.message)?;
+        }
+
+        let values = self.network_values.entry(node_id).or_default();
+        match text {
+            Some(text) => values.insert(network.to_owned(), text),
+            None => values.remove(network),
+        };
+
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(node_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        if values.is_empty() {
+            props.remove(NETWORK_VALUES_MARKER);
+            self.network_values.remove(&node_id);
+        } else {
+            props.insert(
+                NETWORK_VALUES_MARKER.into(),
+                serde_json::to_value(&*values).unwrap(),
+            );
+        }
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::UpdateNode((node_id.0, props)),
+        )))
+        .unwrap();
+
+        Ok(())
+    }
+
+    pub fn is_missing_network_value(&self, node_id: &NodeId) -> bool {
+        self.network_values
+            .get(node_id)
+            .map_or(false, |values| !values.contains_key(self.solana_net.name()))
+    }
+
+    /// MARK CONST AS SECRET
+    /// e.g. an auth token typed as a string, redacted on export
+    ///
+    pub fn set_const_secret(&mut self, node_id: NodeId, is_secret: bool) {
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(node_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        if is_secret {
+            props.insert(SECRET_MARKER.into(), JsonValue::Bool(true));
+            self.secret_node_ids.insert(node_id);
+        } else {
+            props.remove(SECRET_MARKER);
+            self.secret_node_ids.remove(&node_id);
+        }
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::UpdateNode((node_id.0, props)),
+        )))
+        .unwrap();
+    }
+
+    /// FLOW INPUTS
+    /// consts declared as parameters of the flow, given when deploying
+    ///
+    pub fn set_flow_input(&mut self, node_id: NodeId, name: Option<String>) -> Result<(), String> {
+        match self.nodes.get(&node_id) {
+            Some(NodeModel::Widget(data)) if data.command_name.as_deref() == Some("const") => {}
+            _ => return Err("only const nodes can be flow inputs".to_owned()),
+        }
+
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(node_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        match name {
+            Some(name) => {
+                let name = name.trim().to_owned();
+                flow_input::validate_name(&name)?;
+                if self
+                    .flow_inputs
+                    .iter()
+                    .any(|(other_id, other)| *other_id != node_id && *other == name)
+                {
+                    return Err(format!("input {} already exists", name));
+                }
+
+                props.insert(FLOW_INPUT_MARKER.into(), JsonValue::String(name.clone()));
+                self.flow_inputs.insert(node_id, name);
+            }
+            None => {
+                props.remove(FLOW_INPUT_MARKER);
+                self.flow_inputs.remove(&node_id);
+            }
+        }
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::UpdateNode((node_id.0, props)),
+        )))
+        .unwrap();
+
+        Ok(())
+    }
+
+    pub fn flow_input_list(&self) -> Vec<FlowInput> {
+        let mut inputs: Vec<FlowInput> = self
+            .flow_inputs
+            .iter()
+            .filter_map(|(node_id, name)| {
+                let data = self.nodes.get(node_id)?.data();
+                Some(FlowInput {
+                    node_id: node_id.0.to_string(),
+                    name: name.clone(),
+                    kind: data.additional_data.clone(),
+                    default: const_text_payload(&data.text),
+                })
+            })
+            .collect();
+        inputs.sort_by(|a, b| a.name.cmp(&b.name));
+        inputs
     }
 
     pub fn iter_widget_nodes(&self) -> impl Iterator<Item = (&NodeId, &WidgetNodeData)> {
@@ -701,24 +2008,42 @@ impl Model {
         phrase.split(' ').next().unwrap().to_owned()
     }
 
-    pub fn generate_seed() -> String {
-        use bip39::{Language, Mnemonic, MnemonicType};
-
-        let mnemonic = Mnemonic::new(MnemonicType::Words12, Language::English);
-        let phrase = mnemonic.into_phrase();
+    pub fn generate_seed(config: &SeedConfig) -> Result<String, String> {
+        seed::generate(config)
+    }
 
-        phrase
+    pub fn validate_seed(phrase: &str, config: &SeedConfig) -> Result<(), String> {
+        seed::validate(phrase, config)
     }
 
-    // pub fn validate_seed(phrase: String) -> String {
-    //     use bip39::{Language, Mnemonic, MnemonicType};
+    /// SEED ADDRESS
+    /// pubkey of a seed phrase const, shown next to the node
+    ///
+    pub fn seed_address(&self, node_id: &NodeId, config: &SeedConfig) -> Result<String, String> {
+        let data = match self.nodes.get(node_id) {
+            Some(NodeModel::Widget(data)) => data,
+            None => return Err("node not found".to_owned()),
+        };
 
-    //     let validate = Mnemonic::validate(&phrase, Language::English);
-    // }
+        let phrase = const_text_payload(&data.text)
+            .and_then(|payload| payload.as_str().map(str::to_owned))
+            .ok_or_else(|| "const is not a seed phrase".to_owned())?;
 
-    pub fn new_graph(&mut self) {
-        self.run_status.clear();
+        // secret stays in the vault, only the address is returned
+        let phrase = match parse_reference(&phrase) {
+            Some(vault_id) => self
+                .vault
+                .lock()
+                .unwrap()
+                .secret(vault_id)
+                .map_err(|e| e.to_string())?,
+            None => phrase,
+        };
+
+        seed::derive_pubkey(&phrase, config)
+    }
 
+    pub fn new_graph(&mut self) {
         let properties = json!({
             "name": &Self::random_name(),
             FLOW_GRAPH_MARKER: true,
@@ -739,10 +2064,8 @@ impl Model {
         self.read_graph(GraphId(graph_id));
     }
 
+    // deployed graphs keep running in the background
     pub fn read_graph(&mut self, graph_id: GraphId) {
-        self.undeploy();
-        self.run_status.clear();
-
         // iterate through all graphs and find graph with specific name
         self.graph_list = block_on(self.db.0.execute(Action::Query(QueryKind::ListGraphs)))
             .unwrap()
@@ -772,6 +2095,24 @@ impl Model {
             *graph_id_ref = graph_id
         }
 
+        let graph_props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(graph_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+        self.spending_cap = graph_props
+            .get(SPENDING_CAP_MARKER)
+            .and_then(JsonValue::as_f64);
+        self.watch_trigger = graph_props
+            .get(WATCH_TRIGGER_MARKER)
+            .and_then(|config| serde_json::from_value(config.clone()).ok());
+        self.pending_review = None;
+        self.pending_input_values = Properties::new();
+
         //
         self.bookmarks = HashMap::new();
         for node in graph.nodes.iter() {
@@ -794,8 +2135,19 @@ impl Model {
         // dbg!(self.bookmarks.clone());
 
         self.nodes = HashMap::new();
+        self.secret_node_ids = HashSet::new();
+        self.network_values = HashMap::new();
+        self.flow_inputs = HashMap::new();
+        self.response_node_id = None;
+        self.breakpoints = HashSet::new();
+        self.wallet_graph_id = None;
+        self.wallet = Vec::new();
 
         let get_widget_kind = |properties: &Properties| {
+            if properties.get(LITERAL_NODE_MARKER).is_some() {
+                return None;
+            }
+
             if let Some(command_config) = properties.get(COMMAND_MARKER) {
                 let command_config = serde_json::from_value(command_config.clone()).unwrap();
 
@@ -824,6 +2176,15 @@ impl Model {
                     self.context_node_id = NodeId(node.node_id);
 
                     self.solana_net = ctx.solana_net.into();
+                    self.solana_endpoint = None;
+                    self.wallet_graph_id = Some(GraphId(ctx.wallet_graph));
+
+                    if let Some(endpoint_cfg) = node.properties.get(SOLANA_ENDPOINT_MARKER) {
+                        let endpoint_cfg: SolanaEndpointConfig =
+                            serde_json::from_value(endpoint_cfg.clone()).unwrap();
+                        self.solana_net = endpoint_cfg.solana_net;
+                        self.solana_endpoint = Some(endpoint_cfg.endpoint);
+                    }
 
                     continue;
                 }
@@ -831,6 +2192,30 @@ impl Model {
                 None => continue,
             };
 
+            if node.properties.contains_key(SECRET_MARKER) {
+                self.secret_node_ids.insert(NodeId(node.node_id));
+            }
+
+            if let Some(values) = node.properties.get(NETWORK_VALUES_MARKER) {
+                self.network_values.insert(
+                    NodeId(node.node_id),
+                    serde_json::from_value(values.clone()).unwrap(),
+                );
+            }
+
+            if node.properties.contains_key(RESPONSE_MARKER) {
+                self.response_node_id = Some(NodeId(node.node_id));
+            }
+
+            if node.properties.contains_key(BREAKPOINT_MARKER) {
+                self.breakpoints.insert(NodeId(node.node_id));
+            }
+
+            if let Some(name) = node.properties.get(FLOW_INPUT_MARKER) {
+                self.flow_inputs
+                    .insert(NodeId(node.node_id), name.as_str().unwrap().to_owned());
+            }
+
             // dbg!(node.properties.clone());
             let coords = node.properties.get(COORDS_MARKER).unwrap();
             let coords = serde_json::from_value(coords.clone()).unwrap();
@@ -954,7 +2339,16 @@ impl Model {
             let (inputs, outputs) =
                 Self::generate_ports(*node_id, command_name, cmd, (block_id, coords), width);
 
-            for input in inputs {
+            let literals: HashMap<String, String> = graph
+                .nodes
+                .iter()
+                .find(|node| node.node_id == node_id.0)
+                .and_then(|node| node.properties.get(INPUT_LITERALS_MARKER))
+                .map(|literals| serde_json::from_value(literals.clone()).unwrap())
+                .unwrap_or_default();
+
+            for mut input in inputs {
+                input.literal = literals.get(&input.label).cloned();
                 self.inputs.insert(InputId(generate_uuid_v1()), input);
             }
 
@@ -964,6 +2358,7 @@ impl Model {
         }
 
         self.flow_edges = HashMap::new();
+        self.routes.lock().unwrap().clear();
 
         for (&node_id, node) in self.nodes.iter() {
             let node = match &node {
@@ -1026,6 +2421,16 @@ impl Model {
                     })
                     .unwrap();
 
+                let waypoints = props
+                    .get(WAYPOINTS_MARKER)
+                    .map(|waypoints| serde_json::from_value(waypoints.clone()).unwrap())
+                    .unwrap_or_default();
+
+                let routing = props
+                    .get(EDGE_ROUTING_MARKER)
+                    .map(|routing| serde_json::from_value(routing.clone()).unwrap())
+                    .unwrap_or_default();
+
                 self.flow_edges.insert(
                     EdgeId(edge.id),
                     FlowEdgeModel {
@@ -1033,10 +2438,14 @@ impl Model {
                         output_id: *output_id,
                         edge_type: EdgeType::Flow,
                         db_edge_id: EdgeId(edge.id),
+                        waypoints,
+                        routing,
                     },
                 );
             }
         }
+
+        self.refresh_wallet();
     }
 
     pub fn save_bookmark(&mut self, bookmark_id: BookmarkId, bookmark_model: BookmarkModel) {
@@ -1162,6 +2571,7 @@ impl Model {
                 type_bounds: input_data.3,
                 has_default: input_data.4,
                 default_value: input_data.5,
+                literal: None,
             }
         };
 
@@ -1205,6 +2615,36 @@ impl Model {
         // }
     }
 
+    /// CONST VALIDATION
+    /// checks the value of a const node against its ADDITIONAL_DATA_MARKER kind
+    ///
+    pub fn const_error(&self, node_id: &NodeId) -> Option<ValidationError> {
+        let data = match self.nodes.get(node_id)? {
+            NodeModel::Widget(data) => data,
+        };
+
+        if data.command_name.as_deref() != Some("const") {
+            return None;
+        }
+
+        if let Err(e) = validate_const(&data.additional_data, &data.text) {
+            return Some(e);
+        }
+
+        if is_address_kind(&data.additional_data) {
+            let name = const_text_payload(&data.text)?;
+            let name = name.as_str()?;
+            if !name.is_empty() && self.address_book.get(name).is_none() {
+                return Some(ValidationError {
+                    kind: data.additional_data.clone(),
+                    message: format!("{} is not in the address book", name),
+                });
+            }
+        }
+
+        None
+    }
+
     pub fn set_node_additional_data(&mut self, node_id: &NodeId, additional_data: String) {
         // FIXME: should panic if invalid node_id used
         self.nodes
@@ -1463,7 +2903,12 @@ impl Model {
 
     /// TODO fix delete graph on indra side
     pub fn delete_graph(&mut self, graph_id: GraphId) {
-        //
+        if self.flow_context.is_deployed(graph_id) {
+            block_on(self.flow_context.cancel(graph_id));
+        }
+        block_on(self.flow_context.set_watch_trigger(graph_id, None));
+        self.debug_sessions.remove(&graph_id);
+
         let mut node = block_on(
             self.db
                 .0
@@ -1530,7 +2975,8 @@ impl Model {
     }
 
     /// Remove node from model and db
-    pub fn remove_node(&mut self, node_id: NodeId) {
+    /// returns the flow edges removed with the node
+    pub fn remove_node(&mut self, node_id: NodeId) -> HashSet<EdgeId> {
         // Block > node_edge > Widget >
         // if command, remove Flow edges
         // Graph > node_edge > Block
@@ -1640,8 +3086,9 @@ impl Model {
         // println!("Deleting outputs:{:#?}", outputs_to_remove);
         // println!("Deleting children nodes:{:#?}", children_to_remove);
 
-        for flow_edge_id in flow_edges_to_remove {
-            self.flow_edges.remove(&flow_edge_id).unwrap();
+        for flow_edge_id in flow_edges_to_remove.iter() {
+            self.flow_edges.remove(flow_edge_id).unwrap();
+            self.routes.lock().unwrap().remove(flow_edge_id);
         }
 
         for node_edge_id in node_edges_to_remove {
@@ -1672,30 +3119,28 @@ impl Model {
         .unwrap();
 
         self.nodes.remove(&node_id).unwrap();
+
+        flow_edges_to_remove
     }
 
     // ADD INPUT OUTPUT EDGE
     // TODO when creating edge, toggle start marker
+    // an input takes a single edge, outputs can fan out to many inputs
+    // returns (created edge, removed edge), the removed one is the edge toggled off
+    // or the one the input had before
     pub fn add_or_remove_flow_edge(
         &mut self,
         input_id: InputId,
         output_id: OutputId,
-    ) -> Option<EdgeId> {
-        for (&edge_id, edge) in &self.flow_edges {
-            if edge.input_id == input_id && edge.output_id == output_id {
-                block_on(self.db.0.execute(Action::Mutate(
-                    self.graph_id().0,
-                    MutateKind::DeleteEdge(Edge {
-                        id: edge.db_edge_id.0,
-                        from: self.outputs.get(&edge.output_id).unwrap().command_id.0,
-                        to: self.inputs.get(&edge.input_id).unwrap().command_id.0,
-                    }),
-                )))
-                .unwrap();
+    ) -> (Option<EdgeId>, Option<EdgeId>) {
+        if let Some(edge_id) = self.find_flow_edge(input_id, output_id) {
+            self.remove_flow_edge(edge_id);
+            return (None, Some(edge_id));
+        }
 
-                self.flow_edges.remove(&edge_id);
-                return None;
-            }
+        let replaced_edge_id = self.inbound_flow_edge(input_id);
+        if let Some(replaced_edge_id) = replaced_edge_id {
+            self.remove_flow_edge(replaced_edge_id);
         }
 
         let input_model = self.inputs.get(&input_id).unwrap();
@@ -1753,11 +3198,213 @@ impl Model {
                 output_id,
                 edge_type: EdgeType::Flow,
                 db_edge_id: EdgeId(edge_id.0),
+                waypoints: Vec::new(),
+                routing: EdgeRouting::default(),
             },
         );
         assert!(prev.is_none());
 
-        Some(edge_id)
+        (Some(edge_id), replaced_edge_id)
+    }
+
+    pub fn inbound_flow_edge(&self, input_id: InputId) -> Option<EdgeId> {
+        self.flow_edges
+            .iter()
+            .find(|(_, edge)| edge.input_id == input_id)
+            .map(|(&edge_id, _)| edge_id)
+    }
+
+    pub fn find_flow_edge(&self, input_id: InputId, output_id: OutputId) -> Option<EdgeId> {
+        self.flow_edges
+            .iter()
+            .find(|(_, edge)| edge.input_id == input_id && edge.output_id == output_id)
+            .map(|(&edge_id, _)| edge_id)
+    }
+
+    /// DELETE FLOW EDGE
+    ///
+    pub fn remove_flow_edge(&mut self, edge_id: EdgeId) {
+        // already removed, e.g. with its node
+        let edge = match self.flow_edges.remove(&edge_id) {
+            Some(edge) => edge,
+            None => return,
+        };
+        self.routes.lock().unwrap().remove(&edge_id);
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::DeleteEdge(Edge {
+                id: edge.db_edge_id.0,
+                from: self.outputs.get(&edge.output_id).unwrap().command_id.0,
+                to: self.inputs.get(&edge.input_id).unwrap().command_id.0,
+            }),
+        )))
+        .unwrap();
+    }
+
+    /// MOVE ONE END OF A FLOW EDGE TO ANOTHER PORT
+    /// returns the id of the edge now connecting input and output
+    /// and the edge it replaced on the input, if any
+    ///
+    pub fn rewire_flow_edge(
+        &mut self,
+        edge_id: EdgeId,
+        input_id: InputId,
+        output_id: OutputId,
+    ) -> (EdgeId, Option<EdgeId>) {
+        let edge = self.flow_edges.get(&edge_id).unwrap();
+        if edge.input_id == input_id && edge.output_id == output_id {
+            return (edge_id, None);
+        }
+
+        let waypoints = edge.waypoints.clone();
+        let routing = edge.routing;
+
+        self.remove_flow_edge(edge_id);
+
+        // already connected, keep the existing edge instead of toggling it off
+        if let Some(existing_edge_id) = self.find_flow_edge(input_id, output_id) {
+            return (existing_edge_id, None);
+        }
+
+        let (new_edge_id, replaced_edge_id) = self.add_or_remove_flow_edge(input_id, output_id);
+        let new_edge_id = new_edge_id.unwrap();
+
+        if routing != EdgeRouting::default() || !waypoints.is_empty() {
+            let edge = self.flow_edges.get_mut(&new_edge_id).unwrap();
+            edge.waypoints = waypoints;
+            edge.routing = routing;
+            self.save_flow_edge_to_db(new_edge_id);
+        }
+
+        (new_edge_id, replaced_edge_id)
+    }
+
+    /// SET INPUT LITERAL
+    /// value used when the input is not connected, None removes it
+    ///
+    pub fn set_input_literal(
+        &mut self,
+        input_id: InputId,
+        literal: Option<String>,
+    ) -> Result<(), String> {
+        let input = self.inputs.get(&input_id).unwrap();
+
+        if let Some(literal) = &literal {
+            let value: sunshine_solana::Value =
+                serde_json::from_str(literal).map_err(|e| e.to_string())?;
+            let value = serde_json::to_value(&value).unwrap();
+
+            if !literal_matches_type_bounds(&value, &input.type_bounds) {
+                return Err(format!(
+                    "{} accepts {}, got {}",
+                    input.label, input.type_bounds, literal
+                ));
+            }
+        }
+
+        let command_id = input.command_id;
+        let label = input.label.clone();
+
+        let mut props = block_on(
+            self.db
+                .0
+                .execute(Action::Query(QueryKind::ReadNode(command_id.0))),
+        )
+        .unwrap()
+        .into_node()
+        .unwrap()
+        .properties;
+
+        let mut literals: HashMap<String, String> = props
+            .get(INPUT_LITERALS_MARKER)
+            .map(|literals| serde_json::from_value(literals.clone()).unwrap())
+            .unwrap_or_default();
+
+        match &literal {
+            Some(literal) => literals.insert(label, literal.clone()),
+            None => literals.remove(&label),
+        };
+
+        props.insert(
+            INPUT_LITERALS_MARKER.into(),
+            serde_json::to_value(literals).unwrap(),
+        );
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::UpdateNode((command_id.0, props)),
+        )))
+        .unwrap();
+
+        self.inputs.get_mut(&input_id).unwrap().literal = literal;
+
+        Ok(())
+    }
+
+    /// UPDATE FLOW EDGE WAYPOINTS
+    ///
+    pub fn set_flow_edge_waypoints(&mut self, edge_id: EdgeId, waypoints: Vec<Coords>) {
+        self.flow_edges.get_mut(&edge_id).unwrap().waypoints = waypoints;
+        self.save_flow_edge_to_db(edge_id);
+    }
+
+    /// UPDATE FLOW EDGE ROUTING
+    ///
+    pub fn set_flow_edge_routing(&mut self, edge_id: EdgeId, routing: EdgeRouting) {
+        self.flow_edges.get_mut(&edge_id).unwrap().routing = routing;
+        self.save_flow_edge_to_db(edge_id);
+    }
+
+    // edge properties can't be updated in place, so the db edge is recreated
+    // and the model keeps its edge id
+    fn save_flow_edge_to_db(&mut self, edge_id: EdgeId) {
+        let edge = self.flow_edges.get(&edge_id).unwrap();
+        let input_model = self.inputs.get(&edge.input_id).unwrap();
+        let output_model = self.outputs.get(&edge.output_id).unwrap();
+
+        block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::DeleteEdge(Edge {
+                id: edge.db_edge_id.0,
+                from: output_model.command_id.0,
+                to: input_model.command_id.0,
+            }),
+        )))
+        .unwrap();
+
+        let mut properties = serde_json::Map::new();
+
+        properties.insert(
+            INPUT_ARG_NAME_MARKER.into(),
+            serde_json::to_value(&input_model.label).unwrap(),
+        );
+        properties.insert(
+            OUTPUT_ARG_NAME_MARKER.into(),
+            serde_json::to_value(&output_model.label).unwrap(),
+        );
+        properties.insert(
+            WAYPOINTS_MARKER.into(),
+            serde_json::to_value(&edge.waypoints).unwrap(),
+        );
+        properties.insert(
+            EDGE_ROUTING_MARKER.into(),
+            serde_json::to_value(&edge.routing).unwrap(),
+        );
+
+        let db_edge_id = block_on(self.db.0.execute(Action::Mutate(
+            self.graph_id().0,
+            MutateKind::CreateEdge(CreateEdge {
+                from: output_model.command_id.0,
+                to: input_model.command_id.0,
+                properties,
+            }),
+        )))
+        .unwrap()
+        .as_id()
+        .unwrap();
+
+        self.flow_edges.get_mut(&edge_id).unwrap().db_edge_id = EdgeId(db_edge_id);
     }
 
     /// CREATE NODE EDGE
@@ -1994,6 +3641,133 @@ impl Model {
     //         .0
     // }
 
+    /// polyline of a flow edge from output to input
+    /// nodes in moved_node_ids are shifted by dx, dy while being dragged
+    pub fn flow_edge_polyline(
+        &self,
+        edge_id: EdgeId,
+        edge: &FlowEdgeModel,
+        moved_node_ids: &HashSet<NodeId>,
+        dx: f64,
+        dy: f64,
+    ) -> Vec<Point> {
+        let input = self.inputs.get(&edge.input_id).unwrap();
+        let output = self.outputs.get(&edge.output_id).unwrap();
+
+        let input_data = self.nodes.get(&input.parent_node_id).unwrap().data();
+        let output_data = self.nodes.get(&output.parent_node_id).unwrap().data();
+
+        let offset = |node_id: &NodeId| {
+            if moved_node_ids.contains(node_id) {
+                (dx, dy)
+            } else {
+                (0.0, 0.0)
+            }
+        };
+
+        let (dx1, dy1) = offset(&output.parent_node_id);
+        let (dx2, dy2) = offset(&input.parent_node_id);
+
+        let from = Point {
+            x: (output_data.coords.x + output.local_coords.x + dx1) as i64 + 35,
+            y: (output_data.coords.y + output.local_coords.y + dy1) as i64 + INPUT_SIZE / 2, //half width of input size
+        };
+        let to = Point {
+            x: (input_data.coords.x + input.local_coords.x + dx2) as i64 + 15,
+            y: (input_data.coords.y + input.local_coords.y + dy2) as i64 + INPUT_SIZE / 2,
+        };
+
+        let waypoints: Vec<Point> = edge
+            .waypoints
+            .iter()
+            .map(|coords| Point {
+                x: coords.x as i64,
+                y: coords.y as i64,
+            })
+            .collect();
+
+        match edge.routing {
+            EdgeRouting::Direct => routing::direct_polyline(from, to, &waypoints),
+            EdgeRouting::Orthogonal => {
+                let blocks: Vec<Rect> = self
+                    .iter_widget_nodes()
+                    .filter(|(_, data)| {
+                        matches!(data.kind, WidgetKind::Basic(BasicWidgetKind::Block))
+                    })
+                    .map(|(node_id, data)| {
+                        let (dx, dy) = offset(node_id);
+                        let x = (data.coords.x + dx) as i64;
+                        let y = (data.coords.y + dy) as i64;
+                        Rect {
+                            x1: x,
+                            x2: x + data.dimensions.width,
+                            y1: y,
+                            y2: y + data.dimensions.height,
+                        }
+                    })
+                    .collect();
+
+                // hit tests and views ask for the same routes again and again
+                let key = (from, to, waypoints, blocks);
+                let mut routes = self.routes.lock().unwrap();
+                if let Some((cached_key, points)) = routes.get(&edge_id) {
+                    if *cached_key == key {
+                        return points.clone();
+                    }
+                }
+
+                let (from, to, waypoints, blocks) = &key;
+                let points = routing::orthogonal_polyline(*from, *to, waypoints, blocks);
+                routes.insert(edge_id, (key, points.clone()));
+                points
+            }
+        }
+    }
+
+    pub fn next_flow_edge_at<'a>(
+        &'a self,
+        coords: &'a Coords,
+    ) -> Option<(&'a EdgeId, &'a FlowEdgeModel)> {
+        self.flow_edges_at(coords).next()
+    }
+
+    pub fn flow_edges_at<'a>(
+        &'a self,
+        coords: &'a Coords,
+    ) -> impl Iterator<Item = (&'a EdgeId, &'a FlowEdgeModel)> {
+        self.flow_edges.iter().filter(|(edge_id, edge)| {
+            let points = self.flow_edge_polyline(**edge_id, edge, &HashSet::new(), 0.0, 0.0);
+            let point = Point {
+                x: coords.x as i64,
+                y: coords.y as i64,
+            };
+
+            routing::distance_to_polyline(point, &points) <= EDGE_HIT_DISTANCE
+        })
+    }
+
+    /// when coords are on one end of the edge, returns the port at the other end,
+    /// which stays connected while the grabbed end is dragged to another port
+    pub fn rewire_anchor_at(&self, edge_id: &EdgeId, coords: &Coords) -> Option<PortId> {
+        let edge = self.flow_edges.get(edge_id)?;
+        let points = self.flow_edge_polyline(*edge_id, edge, &HashSet::new(), 0.0, 0.0);
+        let point = Point {
+            x: coords.x as i64,
+            y: coords.y as i64,
+        };
+        let is_near = |end: &Point| {
+            routing::distance_to_polyline(point, std::slice::from_ref(end)) <= EDGE_HIT_DISTANCE
+        };
+
+        if points.last().map_or(false, is_near) {
+            Some(PortId::Output(edge.output_id))
+        } else if points.first().map_or(false, is_near) {
+            Some(PortId::Input(edge.input_id))
+        } else {
+            None
+        }
+    }
+
     pub fn inputs_at<'a>(
         &'a self,
         coords: &'a Coords,
@@ -2140,6 +3914,35 @@ impl Model {
     }
 }
 
+// address book graph is created on first launch
+fn load_address_book(db: Db) -> (GraphId, AddressBook) {
+    let address_book = block_on(db.0.execute(Action::Query(QueryKind::ListGraphs)))
+        .unwrap()
+        .into_node_list()
+        .unwrap()
+        .into_iter()
+        .find(|(_, properties)| properties.contains_key(ADDRESS_BOOK_MARKER));
+
+    match address_book {
+        Some((graph_id, properties)) => {
+            (GraphId(graph_id), AddressBook::from_properties(&properties))
+        }
+        None => {
+            let properties = match json!({ ADDRESS_BOOK_MARKER: true }) {
+                JsonValue::Object(props) => props,
+                _ => unreachable!(),
+            };
+
+            let graph_id = block_on(db.0.execute(Action::CreateGraph(properties)))
+                .unwrap()
+                .as_id()
+                .unwrap();
+
+            (GraphId(graph_id), AddressBook::default())
+        }
+    }
+}
+
 // Create wallet graph and context node
 pub fn create_wallet_and_context(db: Db, graph_id: GraphId) -> ContextConfig {
     // create wallet graph
@@ -2170,6 +3973,68 @@ pub fn create_wallet_and_context(db: Db, graph_id: GraphId) -> ContextConfig {
     solana_context_config
 }
 
+// replace the value of a secret const with a placeholder
+fn redact_const(properties: &mut Properties, is_secret: bool) {
+    let is_const = properties
+        .get(COMMAND_NAME_MARKER)
+        .and_then(JsonValue::as_str)
+        == Some("const");
+    let kind = properties
+        .get(ADDITIONAL_DATA_MARKER)
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default();
+
+    if !is_const || !(is_secret || is_secret_kind(kind)) {
+        return;
+    }
+
+    let redacted = SimpleCommand::Const(sunshine_solana::Value::String(
+        REDACTED_PLACEHOLDER.to_owned(),
+    ));
+
+    let text = JsonValue::String(serde_json::to_string(&redacted).unwrap());
+
+    if let Some(JsonValue::Object(values)) = properties.get_mut(NETWORK_VALUES_MARKER) {
+        for value in values.values_mut() {
+            *value = text.clone();
+        }
+    }
+
+    properties.insert(TEXT_MARKER.into(), text);
+    properties.insert(
+        COMMAND_MARKER.into(),
+        serde_json::to_value(CommandConfig::Simple(redacted)).unwrap(),
+    );
+}
+
+// replace secret input literals of a command with a placeholder
+fn redact_literals(properties: &mut Properties) {
+    let command_name = properties
+        .get(COMMAND_NAME_MARKER)
+        .and_then(JsonValue::as_str)
+        .unwrap_or_default()
+        .to_owned();
+
+    let literals = match properties.get_mut(INPUT_LITERALS_MARKER) {
+        Some(JsonValue::Object(literals)) => literals,
+        _ => return,
+    };
+
+    let redacted = sunshine_solana::Value::String(REDACTED_PLACEHOLDER.to_owned());
+    let redacted = JsonValue::String(serde_json::to_string(&redacted).unwrap());
+
+    for (label, literal) in literals.iter_mut() {
+        let value: JsonValue = literal
+            .as_str()
+            .and_then(|literal| serde_json::from_str(literal).ok())
+            .unwrap_or_default();
+
+        if is_secret_literal(&command_name, label, &value) {
+            *literal = redacted.clone();
+        }
+    }
+}
+
 impl NodeModel {
     pub fn data(&self) -> &WidgetNodeData {
         match self {
@@ -0,0 +1,197 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::model::NodeId;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// graph a partial run was copied from, on the node of copies older versions made
+pub const PARTIAL_RUN_MARKER: &str = "PARTIAL_RUN_MARKER";
+
+/// command node a debug run pauses before
+pub const BREAKPOINT_MARKER: &str = "BREAKPOINT_MARKER";
+
+/// flow edge from an output to an input of two command nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortEdge {
+    pub from: NodeId,
+    pub output: String, // label of the output
+    pub to: NodeId,
+    pub input: String, // label of the input
+}
+
+/// nodes run again and where their inputs come from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartialRunPlan {
+    pub node_ids: HashSet<NodeId>,
+    pub edges: Vec<PortEdge>,  // between nodes run again
+    pub cached: Vec<PortEdge>, // from nodes not run again, given their latest output
+}
+
+/// the node alone, or the node and every node depending on its outputs
+pub fn plan(node_id: NodeId, with_downstream: bool, edges: &[PortEdge]) -> PartialRunPlan {
+    let node_ids = HashSet::from([node_id]);
+
+    if with_downstream {
+        plan_nodes(downstream(&node_ids, edges), edges)
+    } else {
+        plan_nodes(node_ids, edges)
+    }
+}
+
+pub fn plan_nodes(node_ids: HashSet<NodeId>, edges: &[PortEdge]) -> PartialRunPlan {
+    let (edges, cached) = edges
+        .iter()
+        .filter(|edge| node_ids.contains(&edge.to))
+        .cloned()
+        .partition(|edge| node_ids.contains(&edge.from));
+
+    PartialRunPlan {
+        node_ids,
+        edges,
+        cached,
+    }
+}
+
+// the nodes and every node depending on their outputs
+fn downstream(node_ids: &HashSet<NodeId>, edges: &[PortEdge]) -> HashSet<NodeId> {
+    let mut downstream = node_ids.clone();
+    let mut queue: VecDeque<NodeId> = node_ids.iter().copied().collect();
+
+    while let Some(from) = queue.pop_front() {
+        for edge in edges.iter().filter(|edge| edge.from == from) {
+            if downstream.insert(edge.to) {
+                queue.push_back(edge.to);
+            }
+        }
+    }
+
+    downstream
+}
+
+impl PartialRunPlan {
+    pub fn is_connected_to(&self, node_id: NodeId) -> bool {
+        self.edges
+            .iter()
+            .chain(self.cached.iter())
+            .any(|edge| edge.to == node_id)
+    }
+
+    pub fn is_connected(&self, node_id: NodeId, input: &str) -> bool {
+        self.edges
+            .iter()
+            .chain(self.cached.iter())
+            .any(|edge| edge.to == node_id && edge.input == input)
+    }
+}
+
+/// run of a graph with breakpoints, deployed one segment at a time
+/// as partial runs reusing the outputs of the previous segments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DebugSession {
+    pub done: HashSet<NodeId>,    // ran by the previous segments
+    pub paused_at: Vec<NodeId>,   // nodes whose inputs are all known, run next
+    pub session_id: Uuid,         // shared by the runs of the segments, see ActiveRun::session
+    pub run_id: Option<Uuid>,     // latest run of the graph once the segment started
+    pub is_empty_segment: bool,   // nothing to run before the first breakpoint
+    pub input_values: Properties, // flow inputs given to the deploy
+}
+
+impl DebugSession {
+    /// nodes run until the breakpoints not reached yet,
+    /// or only the nodes the run is paused at when stepping
+    pub fn next_segment(
+        &self,
+        node_ids: &HashSet<NodeId>,
+        edges: &[PortEdge],
+        breakpoints: &HashSet<NodeId>,
+        step: bool,
+    ) -> HashSet<NodeId> {
+        if step && !self.paused_at.is_empty() {
+            return self.paused_at.iter().copied().collect();
+        }
+
+        let stops: HashSet<NodeId> = breakpoints
+            .iter()
+            .filter(|node_id| !self.done.contains(node_id) && !self.paused_at.contains(node_id))
+            .copied()
+            .collect();
+        let blocked = downstream(&stops, edges);
+
+        node_ids
+            .iter()
+            .filter(|node_id| !self.done.contains(node_id) && !blocked.contains(node_id))
+            .copied()
+            .collect()
+    }
+
+    /// marks the segment as run, the run pauses at the nodes it made ready
+    pub fn advance(
+        &mut self,
+        segment: &HashSet<NodeId>,
+        node_ids: &HashSet<NodeId>,
+        edges: &[PortEdge],
+    ) {
+        self.done.extend(segment.iter().copied());
+        self.paused_at = node_ids
+            .iter()
+            .filter(|node_id| !self.done.contains(node_id))
+            .filter(|node_id| {
+                edges
+                    .iter()
+                    .filter(|edge| edge.to == **node_id)
+                    .all(|edge| self.done.contains(&edge.from))
+            })
+            .copied()
+            .collect();
+    }
+}
+
+#[test]
+fn partial_run_reuses_upstream_outputs() {
+    let [keypair, request, print, other] = [(); 4].map(|_| NodeId(uuid::Uuid::new_v4()));
+    let edge = |from, output: &str, to, input: &str| PortEdge {
+        from,
+        output: output.to_owned(),
+        to,
+        input: input.to_owned(),
+    };
+    let edges = vec![
+        edge(keypair, "pubkey", request, "body"),
+        edge(request, "response", print, "print"),
+        edge(keypair, "pubkey", other, "pubkey"),
+    ];
+
+    let single = plan(request, false, &edges);
+    assert_eq!(single.node_ids, HashSet::from([request]));
+    assert!(single.edges.is_empty());
+    assert_eq!(single.cached, vec![edges[0].clone()]);
+    assert!(single.is_connected(request, "body"));
+
+    let downstream = plan(request, true, &edges);
+    assert_eq!(downstream.node_ids, HashSet::from([request, print]));
+    assert_eq!(downstream.edges, vec![edges[1].clone()]);
+    assert_eq!(downstream.cached, vec![edges[0].clone()]);
+
+    // paused before the request, then stepped to the print
+    let node_ids = HashSet::from([keypair, request, print, other]);
+    let breakpoints = HashSet::from([request]);
+    let mut session = DebugSession::default();
+
+    let segment = session.next_segment(&node_ids, &edges, &breakpoints, false);
+    assert_eq!(segment, HashSet::from([keypair, other]));
+    session.advance(&segment, &node_ids, &edges);
+    assert_eq!(session.paused_at, vec![request]);
+
+    let segment = session.next_segment(&node_ids, &edges, &breakpoints, true);
+    assert_eq!(segment, HashSet::from([request]));
+    session.advance(&segment, &node_ids, &edges);
+    assert_eq!(session.paused_at, vec![print]);
+
+    let segment = session.next_segment(&node_ids, &edges, &breakpoints, false);
+    assert_eq!(segment, HashSet::from([print]));
+    session.advance(&segment, &node_ids, &edges);
+    assert!(session.paused_at.is_empty());
+}
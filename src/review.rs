@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::command::commands_map;
+use crate::flow_graph::FlowGraph;
+use crate::model::NodeId;
+use crate::seed::{self, SeedConfig};
+use crate::validation::{const_payload, is_secret_kind};
+use crate::vault::{parse_reference, Vault};
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+// commands moving SOL, checked against the spending cap
+const SOL_TRANSFER_COMMANDS: &[&str] = &["transfer_solana"];
+
+// inputs holding the amount a command moves
+pub const AMOUNT_INPUTS: &[(&str, &str)] = &[
+    ("transfer_solana", "amount"),
+    ("transfer_token", "amount"),
+    ("mint_token", "amount"),
+    ("auction_house_sell", "sale_price"),
+];
+
+/// what a deploy will sign, shown to the user before deploying on mainnet
+///
+/// amounts are estimated from the graph before the run, fees and rent aren't counted,
+/// a run transferring more than the spending cap anyway is canceled by the flow context
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeployReview {
+    pub solana_net: String,
+    pub commands: Vec<SigningCommand>,
+    pub estimated_sol: f64,             // sum of constant SOL transfers
+    pub dynamic_sol_nodes: Vec<String>, // SOL transfers with an amount computed at run time
+    pub spending_cap: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SigningCommand {
+    pub node_id: String,
+    pub command_name: String,
+    pub signers: Vec<Signer>,
+    pub amounts: Vec<Amount>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Signer {
+    pub input: String,
+    pub source: String,         // command name of the node providing the keypair
+    pub pubkey: Option<String>, // None when only known at run time
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Amount {
+    pub input: String,
+    pub value: Option<f64>, // None when computed at run time
+}
+
+impl DeployReview {
+    /// error if the estimated transfers can exceed the spending cap
+    pub fn check_spending_cap(&self) -> Result<(), String> {
+        let cap = match self.spending_cap {
+            Some(cap) => cap,
+            None => return Ok(()),
+        };
+
+        if !self.dynamic_sol_nodes.is_empty() {
+            return Err(format!(
+                "{} transfers have an amount computed at run time, which can't be estimated against the spending cap of {} SOL",
+                self.dynamic_sol_nodes.len(),
+                cap
+            ));
+        }
+
+        if self.estimated_sol > cap {
+            return Err(format!(
+                "flow is estimated to transfer {} SOL, over the spending cap of {} SOL",
+                self.estimated_sol, cap
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// input_texts are the consts of the flow inputs given at deploy time
+pub fn deploy_review(
+    flow: &FlowGraph,
+    input_texts: &HashMap<NodeId, String>,
+    vault: &Vault,
+) -> DeployReview {
+    let mut review = DeployReview {
+        solana_net: flow.solana_net().name().to_owned(),
+        commands: Vec::new(),
+        estimated_sol: 0.0,
+        dynamic_sol_nodes: Vec::new(),
+        spending_cap: flow.spending_cap(),
+    };
+    let commands = commands_map();
+
+    for node_id in flow.nodes.keys() {
+        let command_name = match flow.command_name(*node_id) {
+            Some(command_name) => command_name,
+            None => continue,
+        };
+        let inputs = match commands.get(command_name) {
+            Some(command) => command.inputs(),
+            None => continue,
+        };
+
+        let signers: Vec<Signer> = inputs
+            .iter()
+            .filter(|input| input.acceptable_types().contains("Keypair"))
+            .map(|input| signer(flow, input_texts, vault, *node_id, input.name))
+            .collect();
+
+        if signers.is_empty() {
+            continue;
+        }
+
+        let amounts: Vec<Amount> = inputs
+            .iter()
+            .filter(|input| AMOUNT_INPUTS.contains(&(command_name, input.name)))
+            .map(|input| Amount {
+                input: input.name.to_owned(),
+                value: input_value(flow, input_texts, *node_id, input.name)
+                    .as_ref()
+                    .and_then(number),
+            })
+            .collect();
+
+        if SOL_TRANSFER_COMMANDS.contains(&command_name) {
+            for amount in amounts.iter() {
+                match amount.value {
+                    Some(value) => review.estimated_sol += value,
+                    None => review.dynamic_sol_nodes.push(node_id.0.to_string()),
+                }
+            }
+        }
+
+        review.commands.push(SigningCommand {
+            node_id: node_id.0.to_string(),
+            command_name: command_name.to_owned(),
+            signers,
+            amounts,
+        });
+    }
+
+    review.commands.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    review
+}
+
+fn signer(
+    flow: &FlowGraph,
+    input_texts: &HashMap<NodeId, String>,
+    vault: &Vault,
+    node_id: NodeId,
+    input: &str,
+) -> Signer {
+    let source_node_id = source_node(flow, node_id, input);
+    let source = source_node_id
+        .and_then(|source_node_id| flow.command_name(source_node_id))
+        .unwrap_or("literal")
+        .to_owned();
+
+    let pubkey = source_node_id
+        .filter(|source_node_id| flow.command_name(*source_node_id) == Some("const"))
+        .zip(input_value(flow, input_texts, node_id, input))
+        .and_then(|(source_node_id, value)| {
+            keypair_pubkey(vault, flow.kind(source_node_id), &value)
+        });
+
+    Signer {
+        input: input.to_owned(),
+        source,
+        pubkey,
+    }
+}
+
+// node providing the value of an input, following passthrough outputs
+fn source_node(flow: &FlowGraph, node_id: NodeId, input: &str) -> Option<NodeId> {
+    let edge = flow
+        .edges
+        .iter()
+        .find(|edge| edge.to == node_id && edge.input == input)?;
+
+    // a passthrough output gives the value of the input of the same name
+    let command = flow
+        .command_name(edge.from)
+        .and_then(|name| commands_map().get(name).copied());
+    if let Some(command) = command {
+        let is_passthrough = command
+            .outputs()
+            .iter()
+            .any(|output| output.name == edge.output && *output.passthrough);
+        let has_input = command
+            .inputs()
+            .iter()
+            .any(|input| input.name == edge.output);
+        if is_passthrough && has_input {
+            return source_node(flow, edge.from, &edge.output);
+        }
+    }
+
+    Some(edge.from)
+}
+
+// constant value of an input, from a literal or a const node
+fn input_value(
+    flow: &FlowGraph,
+    input_texts: &HashMap<NodeId, String>,
+    node_id: NodeId,
+    input: &str,
+) -> Option<JsonValue> {
+    let source_node_id = match source_node(flow, node_id, input) {
+        Some(source_node_id) => source_node_id,
+        None => {
+            return flow
+                .literals(node_id)
+                .get(input)
+                .and_then(|literal| serde_json::from_str(literal).ok())
+        }
+    };
+
+    if flow.command_name(source_node_id) != Some("const") {
+        return None;
+    }
+
+    // value given at deploy time or deployed on the selected network
+    let text = input_texts
+        .get(&source_node_id)
+        .map(String::as_str)
+        .or_else(|| flow.network_text(source_node_id, flow.solana_net()))
+        .unwrap_or_else(|| flow.text(source_node_id));
+
+    serde_json::from_str(text).ok()
+}
+
+fn keypair_pubkey(vault: &Vault, kind: &str, value: &JsonValue) -> Option<String> {
+    let text = const_payload(value).as_str()?;
+
+    // secret is only read when the vault is unlocked
+    let text = match parse_reference(text) {
+        Some(vault_id) => vault.secret(vault_id).ok()?,
+        None => text.to_owned(),
+    };
+
+    if is_secret_kind(kind) && text.trim().contains(' ') {
+        return seed::derive_pubkey(&text, &SeedConfig::default()).ok();
+    }
+
+    // base58 keypair, the public key is the last 32 bytes
+    let bytes = bs58::decode(text.trim()).into_vec().ok()?;
+    match bytes.len() {
+        64 => Some(bs58::encode(&bytes[32..]).into_string()),
+        _ => None,
+    }
+}
+
+/// SOL a node transfers, from the inputs the runner logged for it
+pub fn sol_transferred(command_name: &str, inputs: &Properties) -> Option<f64> {
+    if !SOL_TRANSFER_COMMANDS.contains(&command_name) {
+        return None;
+    }
+
+    AMOUNT_INPUTS
+        .iter()
+        .filter(|(name, _)| *name == command_name)
+        .map(|(_, input)| inputs.get(*input).and_then(number))
+        .sum()
+}
+
+pub fn number(value: &JsonValue) -> Option<f64> {
+    match const_payload(value) {
+        JsonValue::Number(number) => number.as_f64(),
+        JsonValue::String(number) => number.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[test]
+fn spending_cap() {
+    let mut review = DeployReview {
+        solana_net: "mainnet".into(),
+        commands: vec![],
+        estimated_sol: 1.5,
+        dynamic_sol_nodes: vec![],
+        spending_cap: None,
+    };
+    assert!(review.check_spending_cap().is_ok());
+
+    review.spending_cap = Some(2.0);
+    assert!(review.check_spending_cap().is_ok());
+
+    review.estimated_sol = 2.5;
+    assert!(review.check_spending_cap().is_err());
+
+    review.estimated_sol = 0.0;
+    review.dynamic_sol_nodes.push("node".into());
+    assert!(review.check_spending_cap().is_err());
+}
+
+#[test]
+fn sol_transferred_by_logged_inputs() {
+    let inputs: Properties = serde_json::from_value(serde_json::json!({
+        "amount": { "F64": 1.5 },
+        "recipient": { "Pubkey": "11111111111111111111111111111111" },
+    }))
+    .unwrap();
+
+    assert_eq!(sol_transferred("transfer_solana", &inputs), Some(1.5));
+    assert_eq!(sol_transferred("transfer_token", &inputs), None);
+    assert_eq!(sol_transferred("transfer_solana", &Properties::new()), None);
+}
@@ -0,0 +1,289 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::utils::Rect;
+
+/// distance kept between a routed edge and the blocks it goes around
+pub const ROUTE_MARGIN: i64 = 20;
+
+/// extra cost of a turn, so routes prefer fewer bends over slightly shorter length
+const BEND_PENALTY: i64 = 40;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum Direction {
+    Start,
+    Horizontal,
+    Vertical,
+}
+
+/// straight segments from -> waypoints -> to
+pub fn direct_polyline(from: Point, to: Point, waypoints: &[Point]) -> Vec<Point> {
+    let mut points = Vec::with_capacity(waypoints.len() + 2);
+    points.push(from);
+    points.extend_from_slice(waypoints);
+    points.push(to);
+    simplify(points)
+}
+
+/// horizontal and vertical segments from -> waypoints -> to, going around obstacles
+///
+/// the edge leaves `from` to the right (outputs) and enters `to` from the left (inputs)
+pub fn orthogonal_polyline(
+    from: Point,
+    to: Point,
+    waypoints: &[Point],
+    obstacles: &[Rect],
+) -> Vec<Point> {
+    let start = escape(from, 1, obstacles);
+    let end = escape(to, -1, obstacles);
+
+    let mut stops = Vec::with_capacity(waypoints.len() + 2);
+    stops.push(start);
+    stops.extend_from_slice(waypoints);
+    stops.push(end);
+
+    let mut points = vec![from, start];
+    for leg in stops.windows(2) {
+        points.extend(route_leg(leg[0], leg[1], obstacles).into_iter().skip(1));
+    }
+    points.push(to);
+
+    simplify(points)
+}
+
+/// shortest distance from a point to any segment of the polyline
+pub fn distance_to_polyline(point: Point, points: &[Point]) -> f64 {
+    match points {
+        [] => f64::INFINITY,
+        [single] => distance_to_segment(point, *single, *single),
+        _ => points
+            .windows(2)
+            .map(|segment| distance_to_segment(point, segment[0], segment[1]))
+            .fold(f64::INFINITY, f64::min),
+    }
+}
+
+fn distance_to_segment(point: Point, a: Point, b: Point) -> f64 {
+    let (px, py) = (point.x as f64, point.y as f64);
+    let (ax, ay) = (a.x as f64, a.y as f64);
+    let (dx, dy) = ((b.x - a.x) as f64, (b.y - a.y) as f64);
+
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((px - ax) * dx + (py - ay) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+
+    ((px - (ax + t * dx)).powi(2) + (py - (ay + t * dy)).powi(2)).sqrt()
+}
+
+/// move horizontally out of every obstacle the point is in
+fn escape(point: Point, direction: i64, obstacles: &[Rect]) -> Point {
+    let mut x = point.x + direction * ROUTE_MARGIN;
+
+    while let Some(rect) = obstacles.iter().find(|rect| rect.contains(x, point.y)) {
+        x = if direction > 0 {
+            rect.x2 + ROUTE_MARGIN
+        } else {
+            rect.x1 - ROUTE_MARGIN
+        };
+    }
+
+    Point { x, y: point.y }
+}
+
+/// shortest path with bend penalty on a sparse grid built from obstacle borders
+fn route_leg(a: Point, b: Point, obstacles: &[Rect]) -> Vec<Point> {
+    if a == b {
+        return vec![a];
+    }
+
+    // a waypoint placed on top of a block should not make the route impossible
+    let obstacles: Vec<&Rect> = obstacles
+        .iter()
+        .filter(|rect| !rect.contains(a.x, a.y) && !rect.contains(b.x, b.y))
+        .collect();
+
+    let mut xs = vec![a.x, b.x];
+    let mut ys = vec![a.y, b.y];
+    for rect in &obstacles {
+        xs.push(rect.x1 - ROUTE_MARGIN);
+        xs.push(rect.x2 + ROUTE_MARGIN);
+        ys.push(rect.y1 - ROUTE_MARGIN);
+        ys.push(rect.y2 + ROUTE_MARGIN);
+    }
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+
+    let index_of = |values: &[i64], value: i64| values.binary_search(&value).unwrap();
+    let start = (index_of(&xs, a.x), index_of(&ys, a.y));
+    let goal = (index_of(&xs, b.x), index_of(&ys, b.y));
+
+    let is_free = |x: i64, y: i64| !obstacles.iter().any(|rect| rect.contains(x, y));
+    let crosses = |p: Point, q: Point| obstacles.iter().any(|rect| segment_crosses(rect, p, q));
+
+    type GridNode = ((usize, usize), Direction);
+
+    let mut costs: HashMap<GridNode, i64> = HashMap::new();
+    let mut previous: HashMap<GridNode, GridNode> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    costs.insert((start, Direction::Start), 0);
+    queue.push(Reverse((0, start, Direction::Start)));
+
+    let mut reached = None;
+
+    while let Some(Reverse((cost, (xi, yi), direction))) = queue.pop() {
+        if (xi, yi) == goal {
+            reached = Some(((xi, yi), direction));
+            break;
+        }
+        if costs
+            .get(&((xi, yi), direction))
+            .map_or(false, |&best| cost > best)
+        {
+            continue;
+        }
+
+        let here = Point {
+            x: xs[xi],
+            y: ys[yi],
+        };
+
+        let neighbours = [
+            (xi.checked_sub(1).map(|xi| (xi, yi)), Direction::Horizontal),
+            (
+                (xi + 1 < xs.len()).then(|| (xi + 1, yi)),
+                Direction::Horizontal,
+            ),
+            (yi.checked_sub(1).map(|yi| (xi, yi)), Direction::Vertical),
+            (
+                (yi + 1 < ys.len()).then(|| (xi, yi + 1)),
+                Direction::Vertical,
+            ),
+        ];
+
+        for (next, next_direction) in neighbours {
+            let (nxi, nyi) = match next {
+                Some(next) => next,
+                None => continue,
+            };
+            let there = Point {
+                x: xs[nxi],
+                y: ys[nyi],
+            };
+            if !is_free(there.x, there.y) || crosses(here, there) {
+                continue;
+            }
+
+            let bend = match direction {
+                Direction::Start => 0,
+                direction if direction == next_direction => 0,
+                _ => BEND_PENALTY,
+            };
+            let next_cost = cost + (there.x - here.x).abs() + (there.y - here.y).abs() + bend;
+
+            let key = ((nxi, nyi), next_direction);
+            if costs.get(&key).map_or(true, |&best| next_cost < best) {
+                costs.insert(key, next_cost);
+                previous.insert(key, ((xi, yi), direction));
+                queue.push(Reverse((next_cost, (nxi, nyi), next_direction)));
+            }
+        }
+    }
+
+    let mut node = match reached {
+        Some(node) => node,
+        // no way around, fall back to a simple elbow
+        None => return vec![a, Point { x: b.x, y: a.y }, b],
+    };
+
+    let mut path = vec![];
+    loop {
+        let ((xi, yi), _) = node;
+        path.push(Point {
+            x: xs[xi],
+            y: ys[yi],
+        });
+        match previous.get(&node) {
+            Some(&prev) => node = prev,
+            None => break,
+        }
+    }
+    path.reverse();
+
+    simplify(path)
+}
+
+/// check whether an axis-aligned segment passes through the inside of a rectangle
+fn segment_crosses(rect: &Rect, p: Point, q: Point) -> bool {
+    let (x1, x2) = (p.x.min(q.x), p.x.max(q.x));
+    let (y1, y2) = (p.y.min(q.y), p.y.max(q.y));
+
+    x1 < rect.x2 && rect.x1 < x2.max(x1 + 1) && y1 < rect.y2 && rect.y1 < y2.max(y1 + 1)
+}
+
+/// remove repeated points and points in the middle of a straight segment
+fn simplify(points: Vec<Point>) -> Vec<Point> {
+    let mut result: Vec<Point> = Vec::with_capacity(points.len());
+
+    for point in points {
+        if result.last() == Some(&point) {
+            continue;
+        }
+        if result.len() >= 2 {
+            let a = result[result.len() - 2];
+            let b = result[result.len() - 1];
+            let is_collinear = (a.x == b.x && b.x == point.x) || (a.y == b.y && b.y == point.y);
+            if is_collinear {
+                result.pop();
+            }
+        }
+        result.push(point);
+    }
+
+    result
+}
+
+#[test]
+fn orthogonal_route_without_obstacles() {
+    let from = Point { x: 0, y: 0 };
+    let to = Point { x: 200, y: 100 };
+
+    let points = orthogonal_polyline(from, to, &[], &[]);
+
+    assert_eq!(points.first(), Some(&from));
+    assert_eq!(points.last(), Some(&to));
+    assert!(points
+        .windows(2)
+        .all(|segment| segment[0].x == segment[1].x || segment[0].y == segment[1].y));
+}
+
+#[test]
+fn orthogonal_route_avoids_blocks() {
+    let from = Point { x: 0, y: 50 };
+    let to = Point { x: 400, y: 50 };
+    let block = Rect {
+        x1: 150,
+        x2: 250,
+        y1: 0,
+        y2: 100,
+    };
+
+    let points = orthogonal_polyline(from, to, &[], &[block.clone()]);
+
+    assert_eq!(points.first(), Some(&from));
+    assert_eq!(points.last(), Some(&to));
+    assert!(points
+        .windows(2)
+        .all(|segment| !segment_crosses(&block, segment[0], segment[1])));
+}
@@ -0,0 +1,579 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value as JsonValue;
+use sunshine_core::msg::{Action, CreateEdge, MutateKind, QueryKind};
+use sunshine_core::store::Datastore;
+use sunshine_solana::{
+    commands::simple::Command as SimpleCommand, CommandConfig, COMMAND_MARKER, COMMAND_NAME_MARKER,
+    CTX_EDGE_MARKER, INPUT_ARG_NAME_MARKER, OUTPUT_ARG_NAME_MARKER, RUN_ID_MARKER,
+    START_NODE_MARKER,
+};
+use uuid::Uuid;
+
+use crate::address_book::AddressBook;
+use crate::flow_graph::FlowGraph;
+use crate::model::{
+    DeployError, GraphId, NodeId, ADDITIONAL_DATA_MARKER, FLOW_GRAPH_MARKER, LITERAL_NODE_MARKER,
+    NETWORK_VALUES_MARKER, SECRET_MARKER,
+};
+use crate::partial_run::{PartialRunPlan, PortEdge, PARTIAL_RUN_MARKER};
+use crate::validation::{const_payload_mut, is_address_kind, is_secret_kind};
+use crate::vault::Vault;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// id of the flow a run copy was made from, on the graph node of the copy
+pub const RUN_COPY_MARKER: &str = "RUN_COPY_MARKER";
+
+/// copied node whose config held a vault secret or a secret flow input when deployed,
+/// the runner logs the config it read, see scrub_log
+pub const SECRET_RESOLVED_MARKER: &str = "SECRET_RESOLVED_MARKER";
+
+const REDACTED: &str = "<redacted>";
+
+/// graph the runner runs in place of a flow, the flow itself is never changed
+///
+/// literals and cached outputs become consts of the copy, start nodes are computed
+/// for the copied nodes and references are resolved until the runner read the copy
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunCopy {
+    pub graph_id: GraphId,
+    pub node_ids: HashMap<NodeId, NodeId>, // copied node -> node of the flow
+    pub edges: Vec<PortEdge>,              // between nodes of the copy, consts and taps included
+}
+
+/// command configs with the flow inputs, network values, vault secrets and
+/// address book entries in place of what the canvas shows, by node of the flow,
+/// and the nodes whose config now holds a secret
+pub fn resolve_configs(
+    flow: &FlowGraph,
+    node_ids: &HashSet<NodeId>,
+    input_texts: &HashMap<NodeId, String>,
+    network: &str,
+    vault: &Vault,
+    address_book: &AddressBook,
+) -> Result<(HashMap<NodeId, JsonValue>, HashSet<NodeId>), DeployError> {
+    let mut configs = HashMap::new();
+    let mut secret_node_ids = HashSet::new();
+
+    for node_id in node_ids.iter() {
+        let props = match flow.nodes.get(node_id) {
+            Some(props) => props,
+            None => continue,
+        };
+        let mut command_config = match props.get(COMMAND_MARKER) {
+            Some(command_config) => command_config.clone(),
+            None => continue,
+        };
+
+        // parameter given at deploy time, or value of the selected network,
+        // replaces the one shown on the node
+        let mut is_resolved = false;
+        let kind = props
+            .get(ADDITIONAL_DATA_MARKER)
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default();
+        let network_text = props
+            .get(NETWORK_VALUES_MARKER)
+            .and_then(|values| values.get(network))
+            .and_then(JsonValue::as_str);
+        if let Some(text) = input_texts
+            .get(node_id)
+            .map(String::as_str)
+            .or(network_text)
+        {
+            let cfg: SimpleCommand = serde_json::from_str(text)
+                .map_err(|e| DeployError::FlowInput(format!("invalid const: {}", e)))?;
+            command_config = serde_json::to_value(CommandConfig::Simple(cfg)).unwrap();
+            is_resolved = true;
+
+            let is_secret = props.contains_key(SECRET_MARKER) || is_secret_kind(kind);
+            if is_secret && input_texts.contains_key(node_id) {
+                secret_node_ids.insert(*node_id);
+            }
+        }
+
+        if vault.resolve(&mut command_config)? {
+            is_resolved = true;
+            secret_node_ids.insert(*node_id);
+        }
+
+        if is_address_kind(kind) {
+            if let Some(name) = const_payload_mut(&mut command_config) {
+                *name = address_book
+                    .resolve(name, network)
+                    .map_err(DeployError::Address)?;
+                is_resolved = true;
+            }
+        }
+
+        if is_resolved {
+            configs.insert(*node_id, command_config);
+        }
+    }
+
+    Ok((configs, secret_node_ids))
+}
+
+/// new graph with the context node, the nodes of the plan, a const for each value
+/// given to an input and a print node for each tap, see run_outputs::tap_outputs,
+/// configs are the resolved command configs of resolve_configs
+pub async fn create(
+    db: &dyn Datastore,
+    flow: &FlowGraph,
+    plan: &PartialRunPlan,
+    consts: Vec<(NodeId, String, sunshine_solana::Value)>, // node of the flow, input, value
+    taps: &[(NodeId, String)],                             // node of the flow, output
+    configs: &HashMap<NodeId, JsonValue>,
+    secret_node_ids: &HashSet<NodeId>,
+) -> RunCopy {
+    let mut props = Properties::new();
+    props.insert(
+        "name".into(),
+        JsonValue::String(format!("{} (run)", flow.name())),
+    );
+    props.insert(
+        RUN_COPY_MARKER.into(),
+        JsonValue::String(flow.graph_id.0.to_string()),
+    );
+    let graph_id = db
+        .execute(Action::CreateGraph(props))
+        .await
+        .unwrap()
+        .as_id()
+        .unwrap();
+
+    // node of the flow -> copy
+    let mut copy_ids = HashMap::new();
+
+    let ctx_copy_id = match &flow.ctx_node {
+        Some((_, props)) => Some(create_node(db, graph_id, props.clone()).await),
+        None => None,
+    };
+
+    for node_id in plan.node_ids.iter() {
+        let mut props = match flow.nodes.get(node_id) {
+            Some(props) => props.clone(),
+            None => continue,
+        };
+        if let Some(command_config) = configs.get(node_id) {
+            props.insert(COMMAND_MARKER.into(), command_config.clone());
+        }
+        if secret_node_ids.contains(node_id) {
+            props.insert(SECRET_RESOLVED_MARKER.into(), JsonValue::Bool(true));
+        }
+
+        // the runner starts with the nodes without inputs
+        let has_inputs =
+            plan.is_connected_to(*node_id) || consts.iter().any(|(to, _, _)| to == node_id);
+        if has_inputs {
+            props.remove(START_NODE_MARKER);
+        } else {
+            props.insert(START_NODE_MARKER.into(), JsonValue::Bool(true));
+        }
+
+        let copy_id = create_node(db, graph_id, props).await;
+        copy_ids.insert(*node_id, copy_id);
+    }
+
+    if let Some(ctx_copy_id) = ctx_copy_id {
+        for to in flow.ctx_edges.iter().filter_map(|to| copy_ids.get(to)) {
+            let mut properties = Properties::new();
+            properties.insert(CTX_EDGE_MARKER.into(), JsonValue::Bool(true));
+            db.execute(Action::Mutate(
+                graph_id,
+                MutateKind::CreateEdge(CreateEdge {
+                    from: ctx_copy_id,
+                    to: *to,
+                    properties,
+                }),
+            ))
+            .await
+            .unwrap();
+        }
+    }
+
+    let mut edges = Vec::new();
+    for edge in plan.edges.iter() {
+        let edge = PortEdge {
+            from: NodeId(copy_ids[&edge.from]),
+            to: NodeId(copy_ids[&edge.to]),
+            ..edge.clone()
+        };
+        create_port_edge(db, graph_id, &edge).await;
+        edges.push(edge);
+    }
+
+    for (to, input, value) in consts {
+        let mut props = Properties::new();
+        props.insert(
+            COMMAND_MARKER.into(),
+            serde_json::to_value(CommandConfig::Simple(SimpleCommand::Const(value))).unwrap(),
+        );
+        props.insert(
+            COMMAND_NAME_MARKER.into(),
+            JsonValue::String("const".to_owned()),
+        );
+        props.insert(START_NODE_MARKER.into(), JsonValue::Bool(true));
+
+        let edge = PortEdge {
+            from: NodeId(create_node(db, graph_id, props).await),
+            output: "output".to_owned(),
+            to: NodeId(copy_ids[&to]),
+            input,
+        };
+        create_port_edge(db, graph_id, &edge).await;
+        edges.push(edge);
+    }
+
+    for (from, output) in taps {
+        let mut props = Properties::new();
+        props.insert(
+            COMMAND_MARKER.into(),
+            serde_json::to_value(CommandConfig::Simple(SimpleCommand::Print)).unwrap(),
+        );
+        props.insert(
+            COMMAND_NAME_MARKER.into(),
+            JsonValue::String("print".to_owned()),
+        );
+
+        let edge = PortEdge {
+            from: NodeId(copy_ids[from]),
+            output: output.clone(),
+            to: NodeId(create_node(db, graph_id, props).await),
+            input: "print".to_owned(),
+        };
+        create_port_edge(db, graph_id, &edge).await;
+        edges.push(edge);
+    }
+
+    RunCopy {
+        graph_id: GraphId(graph_id),
+        node_ids: copy_ids
+            .into_iter()
+            .map(|(node_id, copy_id)| (NodeId(copy_id), node_id))
+            .collect(),
+        edges,
+    }
+}
+
+/// puts the configs of the flow back once the runner read the copy,
+/// secrets don't need to stay in the db
+pub async fn restore(
+    db: &dyn Datastore,
+    flow: &FlowGraph,
+    copy: &RunCopy,
+    configs: &HashMap<NodeId, JsonValue>,
+) {
+    for (copy_id, node_id) in copy.node_ids.iter() {
+        if !configs.contains_key(node_id) {
+            continue;
+        }
+        let command_config = match flow.nodes[node_id].get(COMMAND_MARKER) {
+            Some(command_config) => command_config.clone(),
+            None => continue,
+        };
+
+        let mut props = match db
+            .execute(Action::Query(QueryKind::ReadNode(copy_id.0)))
+            .await
+        {
+            Ok(node) => node.into_node().unwrap().properties,
+            Err(_) => continue,
+        };
+        props.insert(COMMAND_MARKER.into(), command_config);
+
+        db.execute(Action::Mutate(
+            copy.graph_id.0,
+            MutateKind::UpdateNode((copy_id.0, props)),
+        ))
+        .await
+        .ok();
+    }
+}
+
+/// replaces the secrets the runner logged with a placeholder, wherever they were
+/// passed along, secrets are what the config of a SECRET_RESOLVED_MARKER node held
+/// when deployed and no longer holds once restored
+pub async fn scrub_log(db: &dyn Datastore, graph_id: GraphId, log_graph: Uuid) {
+    let restored: HashMap<String, JsonValue> = match db.read_graph(graph_id.0).await {
+        Ok(graph) => graph
+            .nodes
+            .into_iter()
+            .filter(|node| node.properties.contains_key(SECRET_RESOLVED_MARKER))
+            .filter_map(|node| {
+                let command_config = node.properties.get(COMMAND_MARKER)?.clone();
+                Some((node.node_id.to_string(), command_config))
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    if restored.is_empty() {
+        return;
+    }
+
+    let log_graph_nodes = match db.read_graph(log_graph).await {
+        Ok(graph) => graph.nodes,
+        Err(_) => return,
+    };
+
+    let mut secrets = Vec::new();
+    for node in log_graph_nodes.iter() {
+        let restored = node
+            .properties
+            .get("original_node_id")
+            .and_then(JsonValue::as_str)
+            .and_then(|node_id| restored.get(node_id));
+        let deployed = node
+            .properties
+            .get("original_props")
+            .and_then(|props| props.get(COMMAND_MARKER));
+        if let (Some(restored), Some(deployed)) = (restored, deployed) {
+            secrets.extend(secret_strings(deployed, restored));
+        }
+    }
+    if secrets.is_empty() {
+        return;
+    }
+
+    for node in log_graph_nodes {
+        let mut props = JsonValue::Object(node.properties);
+        if !redact_strings(&mut props, &secrets) {
+            continue;
+        }
+        let props = match props {
+            JsonValue::Object(props) => props,
+            _ => unreachable!(),
+        };
+
+        db.execute(Action::Mutate(
+            log_graph,
+            MutateKind::UpdateNode((node.node_id, props)),
+        ))
+        .await
+        .ok();
+    }
+}
+
+// strings of the deployed config the restored one doesn't have
+fn secret_strings(deployed: &JsonValue, restored: &JsonValue) -> Vec<String> {
+    fn strings<'a>(value: &'a JsonValue, found: &mut HashSet<&'a str>) {
+        match value {
+            JsonValue::String(text) => {
+                found.insert(text);
+            }
+            JsonValue::Array(values) => values.iter().for_each(|value| strings(value, found)),
+            JsonValue::Object(map) => map.values().for_each(|value| strings(value, found)),
+            _ => {}
+        }
+    }
+
+    let mut deployed_strings = HashSet::new();
+    strings(deployed, &mut deployed_strings);
+    let mut restored_strings = HashSet::new();
+    strings(restored, &mut restored_strings);
+
+    deployed_strings
+        .difference(&restored_strings)
+        .filter(|text| !text.is_empty())
+        .map(|text| text.to_string())
+        .collect()
+}
+
+// true when a value was one of the secrets, only whole values are replaced
+// since a short secret would match parts of unrelated strings
+fn redact_strings(value: &mut JsonValue, secrets: &[String]) -> bool {
+    let is_redacted = redact_values(value, secrets);
+
+    // the node took a secret, what it printed can hold it anywhere
+    if is_redacted {
+        if let Some(print_output) = value.get_mut("__print_output").filter(|v| v.is_string()) {
+            *print_output = JsonValue::String(REDACTED.to_owned());
+        }
+    }
+    is_redacted
+}
+
+fn redact_values(value: &mut JsonValue, secrets: &[String]) -> bool {
+    match value {
+        JsonValue::String(text)
+            if secrets
+                .iter()
+                .any(|secret| secret.as_str() == text.as_str()) =>
+        {
+            *text = REDACTED.to_owned();
+            true
+        }
+        JsonValue::Array(values) => values.iter_mut().fold(false, |is_redacted, value| {
+            redact_values(value, secrets) || is_redacted
+        }),
+        JsonValue::Object(map) => map.values_mut().fold(false, |is_redacted, value| {
+            redact_values(value, secrets) || is_redacted
+        }),
+        _ => false,
+    }
+}
+
+/// removes the nodes of a copy and marks its graph deleted, see Model::delete_graph
+pub async fn delete(db: &dyn Datastore, graph_id: GraphId) {
+    if let Ok(graph) = db.read_graph(graph_id.0).await {
+        for node in graph.nodes {
+            db.execute(Action::Mutate(
+                graph_id.0,
+                MutateKind::DeleteNode(node.node_id),
+            ))
+            .await
+            .ok();
+        }
+    }
+
+    let mut props = match db
+        .execute(Action::Query(QueryKind::ReadNode(graph_id.0)))
+        .await
+    {
+        Ok(node) => node.into_node().unwrap().properties,
+        Err(_) => return,
+    };
+    props.remove(RUN_COPY_MARKER);
+    props.remove(PARTIAL_RUN_MARKER);
+    props.insert("DELETED_GRAPH_MARKER".into(), JsonValue::Bool(true));
+
+    db.execute(Action::Mutate(
+        graph_id.0,
+        MutateKind::UpdateNode((graph_id.0, props)),
+    ))
+    .await
+    .ok();
+}
+
+/// copies of runs the app was closed during, and consts older versions
+/// created in the flows themselves for input literals
+pub async fn sweep(db: &dyn Datastore) {
+    let graphs = match db.execute(Action::Query(QueryKind::ListGraphs)).await {
+        Ok(graphs) => graphs.into_node_list().unwrap(),
+        Err(_) => return,
+    };
+
+    for (graph_id, props) in graphs {
+        if props.contains_key(RUN_COPY_MARKER) || props.contains_key(PARTIAL_RUN_MARKER) {
+            for log_graph in log_graphs(db, GraphId(graph_id)).await {
+                scrub_log(db, GraphId(graph_id), log_graph).await;
+            }
+            delete(db, GraphId(graph_id)).await;
+            continue;
+        }
+        if !props.contains_key(FLOW_GRAPH_MARKER) {
+            continue;
+        }
+
+        let graph = match db.read_graph(graph_id).await {
+            Ok(graph) => graph,
+            Err(_) => continue,
+        };
+        for node in graph.nodes {
+            if node.properties.contains_key(LITERAL_NODE_MARKER) {
+                db.execute(Action::Mutate(
+                    graph_id,
+                    MutateKind::DeleteNode(node.node_id),
+                ))
+                .await
+                .ok();
+            }
+        }
+    }
+}
+
+// log graphs the runner linked to the graph node
+async fn log_graphs(db: &dyn Datastore, graph_id: GraphId) -> Vec<Uuid> {
+    let graph_node = match db
+        .execute(Action::Query(QueryKind::ReadNode(graph_id.0)))
+        .await
+    {
+        Ok(node) => node.into_node().unwrap(),
+        Err(_) => return Vec::new(),
+    };
+
+    let mut log_graphs = Vec::new();
+    for edge in graph_node.outbound_edges {
+        let is_run = db
+            .read_edge_properties(edge)
+            .await
+            .map_or(false, |props| props.contains_key(RUN_ID_MARKER));
+        if is_run {
+            log_graphs.push(edge.to);
+        }
+    }
+    log_graphs
+}
+
+async fn create_node(db: &dyn Datastore, graph_id: Uuid, props: Properties) -> Uuid {
+    db.execute(Action::Mutate(graph_id, MutateKind::CreateNode(props)))
+        .await
+        .unwrap()
+        .as_id()
+        .unwrap()
+}
+
+async fn create_port_edge(db: &dyn Datastore, graph_id: Uuid, edge: &PortEdge) {
+    let mut properties = Properties::new();
+    properties.insert(
+        INPUT_ARG_NAME_MARKER.into(),
+        JsonValue::String(edge.input.clone()),
+    );
+    properties.insert(
+        OUTPUT_ARG_NAME_MARKER.into(),
+        JsonValue::String(edge.output.clone()),
+    );
+
+    db.execute(Action::Mutate(
+        graph_id,
+        MutateKind::CreateEdge(CreateEdge {
+            from: edge.from.0,
+            to: edge.to.0,
+            properties,
+        }),
+    ))
+    .await
+    .unwrap();
+}
+
+#[test]
+fn run_copy_scrubs_secrets() {
+    let deployed = serde_json::json!({ "Simple": { "Const": { "String": "secret words" } } });
+    let restored = serde_json::json!({ "Simple": { "Const": { "String": "vault:1234" } } });
+    let secrets = secret_strings(&deployed, &restored);
+    assert_eq!(secrets, vec!["secret words".to_owned()]);
+
+    let mut log_node = serde_json::json!({
+        "original_props": { "COMMAND_MARKER": deployed },
+        "inputs": { "seed_phrase": { "String": "secret words" } },
+        "__print_output": "got secret words",
+        "state": { "Success": 12 },
+    });
+    assert!(redact_strings(&mut log_node, &secrets));
+    assert_eq!(
+        log_node,
+        serde_json::json!({
+            "original_props": { "COMMAND_MARKER": { "Simple": { "Const": { "String": "<redacted>" } } } },
+            "inputs": { "seed_phrase": { "String": "<redacted>" } },
+            "__print_output": "<redacted>",
+            "state": { "Success": 12 },
+        })
+    );
+    assert!(!redact_strings(&mut log_node, &secrets));
+
+    // a short secret leaves other strings whole
+    let secrets = vec!["ab".to_owned()];
+    let mut log_node = serde_json::json!({
+        "original_node_id": "cab",
+        "inputs": { "token": { "String": "ab" }, "name": { "String": "cabin" } },
+    });
+    assert!(redact_strings(&mut log_node, &secrets));
+    assert_eq!(
+        log_node,
+        serde_json::json!({
+            "original_node_id": "cab",
+            "inputs": { "token": { "String": "<redacted>" }, "name": { "String": "cabin" } },
+        })
+    );
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value as JsonValue};
+
+use crate::command::commands_map;
+use crate::flow_graph::FlowGraph;
+use crate::model::NodeId;
+use crate::partial_run::{PartialRunPlan, PortEdge};
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// the runner only logs the inputs of each node, the value of an output is the
+/// input it was passed to, outputs nothing reads get a print node in the run copy
+///
+/// typed outputs of the planned nodes no edge of the plan reads, keypairs aren't printed
+pub fn tap_outputs(flow: &FlowGraph, plan: &PartialRunPlan) -> Vec<(NodeId, String)> {
+    let commands = commands_map();
+    let mut taps = Vec::new();
+
+    for node_id in plan.node_ids.iter() {
+        let command = match flow
+            .command_name(*node_id)
+            .and_then(|name| commands.get(name))
+        {
+            Some(command) => command,
+            None => continue,
+        };
+
+        for output in command.outputs() {
+            if output.r#type.is_empty() || output.r#type == "Keypair" {
+                continue;
+            }
+            let is_read = plan
+                .edges
+                .iter()
+                .any(|edge| edge.from == *node_id && edge.output == output.name);
+            if !is_read {
+                taps.push((*node_id, output.name.to_owned()));
+            }
+        }
+    }
+
+    taps.sort_by(|a, b| (a.0 .0, &a.1).cmp(&(b.0 .0, &b.1)));
+    taps
+}
+
+/// values of the outputs of each node, given the edges of the run copy
+/// and the inputs logged for each node of the copy
+pub fn captured_outputs(
+    edges: &[PortEdge],
+    logged_inputs: &HashMap<NodeId, Properties>,
+) -> HashMap<NodeId, Properties> {
+    let mut outputs: HashMap<NodeId, Properties> = HashMap::new();
+
+    for edge in edges {
+        let value = match logged_inputs
+            .get(&edge.to)
+            .and_then(|inputs| inputs.get(&edge.input))
+        {
+            Some(value) => value,
+            None => continue,
+        };
+        outputs
+            .entry(edge.from)
+            .or_default()
+            .insert(edge.output.clone(), value.clone());
+    }
+
+    outputs
+}
+
+/// value as shown in the app, a keypair is shown as its public key
+pub fn redact(value: &JsonValue) -> JsonValue {
+    let keypair = match value.get("Keypair") {
+        Some(keypair) => keypair,
+        None => return value.clone(),
+    };
+
+    // the public key is the last 32 bytes
+    let bytes = keypair
+        .as_str()
+        .and_then(|keypair| bs58::decode(keypair).into_vec().ok())
+        .filter(|bytes| bytes.len() == 64);
+    match bytes {
+        Some(bytes) => json!({ "Pubkey": bs58::encode(&bytes[32..]).into_string() }),
+        None => json!({ "Keypair": "<redacted>" }),
+    }
+}
+
+pub fn redact_all(values: &Properties) -> Properties {
+    values
+        .iter()
+        .map(|(label, value)| (label.clone(), redact(value)))
+        .collect()
+}
+
+#[test]
+fn run_outputs_captured_from_inputs() {
+    let a = NodeId(uuid::Uuid::new_v4());
+    let b = NodeId(uuid::Uuid::new_v4());
+    let tap = NodeId(uuid::Uuid::new_v4());
+    let edge = |from, output: &str, to, input: &str| PortEdge {
+        from,
+        output: output.to_owned(),
+        to,
+        input: input.to_owned(),
+    };
+    let edges = vec![
+        edge(a, "keypair", b, "payer"),
+        edge(a, "pubkey", b, "recipient"),
+        edge(b, "signature", tap, "print"),
+    ];
+
+    let keypair = bs58::encode([7u8; 64]).into_string();
+    let mut logged_inputs = HashMap::new();
+    logged_inputs.insert(
+        b,
+        json!({ "payer": { "Keypair": keypair }, "recipient": { "Pubkey": "abc" } })
+            .as_object()
+            .unwrap()
+            .clone(),
+    );
+
+    // b hasn't run yet, nothing reached the tap
+    let outputs = captured_outputs(&edges, &logged_inputs);
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[&a]["pubkey"], json!({ "Pubkey": "abc" }));
+    assert_eq!(outputs[&a]["keypair"], json!({ "Keypair": keypair }));
+
+    logged_inputs.insert(
+        tap,
+        json!({ "print": { "String": "sig" } })
+            .as_object()
+            .unwrap()
+            .clone(),
+    );
+    let outputs = captured_outputs(&edges, &logged_inputs);
+    assert_eq!(outputs[&b]["signature"], json!({ "String": "sig" }));
+
+    let shown = redact_all(&outputs[&a]);
+    assert_eq!(
+        shown["keypair"],
+        json!({ "Pubkey": bs58::encode([7u8; 32]).into_string() })
+    );
+    assert_eq!(shown["pubkey"], json!({ "Pubkey": "abc" }));
+    assert_eq!(
+        redact(&json!({ "Keypair": "broken" })),
+        json!({ "Keypair": "<redacted>" })
+    );
+}
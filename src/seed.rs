@@ -0,0 +1,97 @@
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use ed25519_dalek_bip32::{DerivationPath, ExtendedSecretKey};
+use serde::Deserialize;
+
+/// path used by phantom, solflare and `solana-keygen --derivation-path`
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// options of a seed phrase, sent by the ui as json
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SeedConfig {
+    pub word_count: usize,               // 12, 15, 18, 21 or 24
+    pub language: String,                // bip39 language code, e.g. "en", "fr", "ja"
+    pub passphrase: String,              // optional "25th word"
+    pub derivation_path: Option<String>, // None uses the first 32 bytes of the seed
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self {
+            word_count: 12,
+            language: "en".to_owned(),
+            passphrase: String::new(),
+            derivation_path: Some(DEFAULT_DERIVATION_PATH.to_owned()),
+        }
+    }
+}
+
+impl SeedConfig {
+    fn language(&self) -> Result<Language, String> {
+        Language::from_language_code(&self.language)
+            .ok_or_else(|| format!("unsupported language: {}", self.language))
+    }
+}
+
+/// languages enabled in tiny-bip39
+pub const LANGUAGE_CODES: [&str; 8] = ["en", "zh-hans", "zh-hant", "fr", "it", "ja", "ko", "es"];
+
+pub fn generate(config: &SeedConfig) -> Result<String, String> {
+    let mnemonic_type =
+        MnemonicType::for_word_count(config.word_count).map_err(|e| e.to_string())?;
+    let mnemonic = Mnemonic::new(mnemonic_type, config.language()?);
+
+    Ok(mnemonic.into_phrase())
+}
+
+/// checks word list, word count and checksum
+pub fn validate(phrase: &str, config: &SeedConfig) -> Result<(), String> {
+    Mnemonic::validate(phrase.trim(), config.language()?).map_err(|e| e.to_string())
+}
+
+/// base58 pubkey of the keypair derived from the phrase
+pub fn derive_pubkey(phrase: &str, config: &SeedConfig) -> Result<String, String> {
+    let mnemonic =
+        Mnemonic::from_phrase(phrase.trim(), config.language()?).map_err(|e| e.to_string())?;
+    let seed = Seed::new(&mnemonic, &config.passphrase);
+
+    let public_key = match &config.derivation_path {
+        Some(path) => {
+            let path: DerivationPath = path
+                .parse()
+                .map_err(|e| format!("invalid derivation path: {:?}", e))?;
+            ExtendedSecretKey::from_seed(seed.as_bytes())
+                .and_then(|key| key.derive(&path))
+                .map_err(|e| e.to_string())?
+                .public_key()
+        }
+        None => {
+            let secret = ed25519_dalek_bip32::SecretKey::from_bytes(&seed.as_bytes()[..32])
+                .map_err(|e| e.to_string())?;
+            (&secret).into()
+        }
+    };
+
+    Ok(bs58::encode(public_key.as_bytes()).into_string())
+}
+
+#[test]
+fn seed_generation_and_derivation() {
+    let config = SeedConfig {
+        word_count: 24,
+        ..SeedConfig::default()
+    };
+    let phrase = generate(&config).unwrap();
+    assert_eq!(phrase.split(' ').count(), 24);
+    assert!(validate(&phrase, &config).is_ok());
+
+    let phrase = "abandon abandon abandon abandon abandon abandon \
+                  abandon abandon abandon abandon abandon about";
+    let config = SeedConfig::default();
+    assert!(validate(phrase, &config).is_ok());
+    assert!(validate(&phrase.replace("about", "abandon"), &config).is_err());
+    assert_eq!(
+        derive_pubkey(phrase, &config).unwrap(),
+        "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+    );
+}
@@ -7,6 +7,7 @@ use crate::event::Coords;
 use crate::event::Event;
 use crate::input::{CapturedLifetime, Context, Input, MappingKind};
 
+use crate::model::EdgeId;
 use crate::model::NodeModel;
 use crate::model::PortId;
 use crate::model::WidgetKind;
@@ -20,6 +21,7 @@ pub struct State {
 
     pub selected_node_ids: HashSet<NodeId>,
     pub active_node: Option<NodeId>,
+    pub selected_edge_ids: HashSet<EdgeId>, // flow edges
 
     // viewport manipulation
     pub transform: Transform,
@@ -62,6 +64,7 @@ impl State {
             ui_state: UiState::Default,
             selected_node_ids: HashSet::new(),
             active_node: None,
+            selected_edge_ids: HashSet::new(),
             transform: Transform::default(), // req_id: u64::default(),
             transform_screenshot: Transform::default(), // req_id: u64::default(),
             canvas: Canvas {
@@ -108,8 +111,13 @@ impl State {
         self.selected_node_ids.iter()
     }
 
+    pub fn selected_edge_ids(&self) -> impl Iterator<Item = &EdgeId> {
+        self.selected_edge_ids.iter()
+    }
+
     pub fn clear_selection(&mut self) {
         self.selected_node_ids.clear();
+        self.selected_edge_ids.clear();
         self.active_node = None;
         //dbg!(&self.selected_node_ids, self.active_node);
         // self.update_selection();
@@ -141,6 +149,16 @@ impl State {
         }
     }
 
+    pub fn add_edge_to_selection(&mut self, edge_id: EdgeId) {
+        self.selected_edge_ids.insert(edge_id);
+    }
+
+    pub fn add_or_remove_edge_from_selection(&mut self, edge_id: EdgeId) {
+        if !self.selected_edge_ids.remove(&edge_id) {
+            self.selected_edge_ids.insert(edge_id);
+        }
+    }
+
     /*pub fn update_active_node(&mut self, node_id: NodeId) {
         self.active_node = Some(node_id);
     }*/
@@ -156,6 +174,7 @@ impl State {
                 transform: self.transform,
                 ui_state: &self.ui_state,
                 selected_node_ids: &self.selected_node_ids,
+                selected_edge_ids: &self.selected_edge_ids,
                 mapping_kind: self.mapping_kind,
             },
         )
@@ -172,6 +191,7 @@ impl State {
                 transform: self.transform,
                 ui_state: &self.ui_state,
                 selected_node_ids: &self.selected_node_ids,
+                selected_edge_ids: &self.selected_edge_ids,
                 mapping_kind: self.mapping_kind,
             },
         )
@@ -237,6 +257,8 @@ pub enum UiState {
     NodeMove(Coords, Coords),
     MaybeEdge(PortId),
     Edge(PortId, Coords),
+    MaybeEdgeRewire(EdgeId, PortId), // port at the end that stays connected
+    EdgeRewire(EdgeId, PortId, Coords),
     UiInput,
     CommandInput(String),
 }
@@ -15,7 +15,7 @@ pub fn merge_json(a: &mut JsonValue, b: &JsonValue) {
 }
 
 /// check whether a point is within a rectangle
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Rect {
     pub x1: i64,
     pub x2: i64,
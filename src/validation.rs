@@ -0,0 +1,194 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::model::REDACTED_PLACEHOLDER;
+use crate::seed::{self, SeedConfig, LANGUAGE_CODES};
+use crate::vault::parse_reference;
+
+/// error shown on a const node, serialized into NodeView.error
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationError {
+    pub kind: String, // ADDITIONAL_DATA_MARKER of the node
+    pub message: String,
+}
+
+/// validate the text of a const node against the kind selected in the ui
+///
+/// text is a serialized const command, e.g. { "Const": { "String": "..." } }
+pub fn validate_const(kind: &str, text: &str) -> Result<(), ValidationError> {
+    if text.is_empty() {
+        return Ok(());
+    }
+
+    let error = |message: String| ValidationError {
+        kind: kind.to_owned(),
+        message,
+    };
+
+    let value: JsonValue =
+        serde_json::from_str(text).map_err(|e| error(format!("invalid value: {}", e)))?;
+    let payload = const_payload(&value);
+
+    // secret is checked when it is stored in the vault
+    if payload.as_str().and_then(parse_reference).is_some() {
+        return Ok(());
+    }
+
+    if payload.as_str() == Some(REDACTED_PLACEHOLDER) {
+        return Err(error("value was redacted on export, enter it again".into()));
+    }
+
+    match normalize_kind(kind).as_str() {
+        "pubkey" => {
+            let pubkey = payload
+                .as_str()
+                .ok_or_else(|| error("expected a string".into()))?;
+            validate_pubkey(pubkey).map_err(error)?;
+        }
+        "json" => {
+            if let Some(json) = payload.as_str() {
+                serde_json::from_str::<JsonValue>(json)
+                    .map_err(|e| error(format!("invalid json: {}", e)))?;
+            }
+        }
+        "bool" | "boolean" => match payload {
+            JsonValue::Bool(_) => {}
+            JsonValue::String(value) if value == "true" || value == "false" => {}
+            _ => return Err(error("expected true or false".into())),
+        },
+        "seed" | "seedphrase" => {
+            let phrase = payload
+                .as_str()
+                .ok_or_else(|| error("expected a string".into()))?;
+
+            // phrase can be in any language, report the english error
+            let is_valid = LANGUAGE_CODES.iter().any(|code| {
+                let config = SeedConfig {
+                    language: code.to_string(),
+                    ..SeedConfig::default()
+                };
+                seed::validate(phrase, &config).is_ok()
+            });
+            if !is_valid {
+                let e = seed::validate(phrase, &SeedConfig::default()).unwrap_err();
+                return Err(error(format!("invalid seed phrase: {}", e)));
+            }
+        }
+        "u8" => check_unsigned(payload, u8::MAX as u64).map_err(error)?,
+        "u16" => check_unsigned(payload, u16::MAX as u64).map_err(error)?,
+        "u64" => check_unsigned(payload, u64::MAX).map_err(error)?,
+        "f64" => {
+            let number = match payload {
+                JsonValue::Number(number) => number.as_f64(),
+                JsonValue::String(number) => number.trim().parse::<f64>().ok(),
+                _ => None,
+            };
+            match number {
+                Some(number) if number.is_finite() => {}
+                _ => return Err(error("expected a number".into())),
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+pub fn validate_pubkey(pubkey: &str) -> Result<(), String> {
+    let bytes = bs58::decode(pubkey.trim())
+        .into_vec()
+        .map_err(|e| format!("invalid base58: {}", e))?;
+    if bytes.len() != 32 {
+        return Err(format!("expected 32 bytes, got {}", bytes.len()));
+    }
+    Ok(())
+}
+
+/// consts of these kinds reference an address book entry by name
+pub fn is_address_kind(kind: &str) -> bool {
+    matches!(normalize_kind(kind).as_str(), "address" | "addressbook")
+}
+
+/// consts of these kinds are redacted on export
+pub fn is_secret_kind(kind: &str) -> bool {
+    matches!(
+        normalize_kind(kind).as_str(),
+        "seed" | "seedphrase" | "keypair"
+    )
+}
+
+// "Seed Phrase" -> "seedphrase"
+fn normalize_kind(kind: &str) -> String {
+    kind.chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// { "Const": { "Pubkey": "..." } } -> "..."
+pub fn const_payload(value: &JsonValue) -> &JsonValue {
+    let mut value = value;
+    for _ in 0..2 {
+        match value {
+            JsonValue::Object(map) if map.len() == 1 => value = map.values().next().unwrap(),
+            _ => break,
+        }
+    }
+    value
+}
+
+// { "Simple": { "Const": { "String": "..." } } } -> "..."
+pub fn const_payload_mut(value: &mut JsonValue) -> Option<&mut String> {
+    match value {
+        JsonValue::Object(map) if map.len() == 1 => const_payload_mut(map.values_mut().next()?),
+        JsonValue::String(payload) => Some(payload),
+        _ => None,
+    }
+}
+
+// { "Const": { "String": "..." } } -> "..."
+pub fn const_text_payload(text: &str) -> Option<JsonValue> {
+    serde_json::from_str::<JsonValue>(text)
+        .ok()
+        .and_then(|value| value.get("Const")?.as_object()?.values().next().cloned())
+}
+
+/// literal is { "String": "<redacted>" }
+pub fn is_redacted_literal(literal: &str) -> bool {
+    serde_json::from_str::<JsonValue>(literal).map_or(false, |value| {
+        value.get("String").and_then(JsonValue::as_str) == Some(REDACTED_PLACEHOLDER)
+    })
+}
+
+/// text of a const is { "Const": { "String": "<redacted>" } }
+pub fn is_redacted_text(text: &str) -> bool {
+    const_text_payload(text).map_or(false, |value| value.as_str() == Some(REDACTED_PLACEHOLDER))
+}
+
+fn check_unsigned(payload: &JsonValue, max: u64) -> Result<(), String> {
+    let number = match payload {
+        JsonValue::Number(number) => number.as_u64(),
+        JsonValue::String(number) => number.trim().parse::<u64>().ok(),
+        _ => None,
+    };
+    match number {
+        Some(number) if number <= max => Ok(()),
+        Some(_) => Err(format!("must be at most {}", max)),
+        None => Err("expected a positive integer".to_owned()),
+    }
+}
+
+#[test]
+fn validate_const_kinds() {
+    assert!(validate_const(
+        "Pubkey",
+        r#"{"Const":{"String":"11111111111111111111111111111111"}}"#
+    )
+    .is_ok());
+    assert!(validate_const("Pubkey", r#"{"Const":{"String":"not a key"}}"#).is_err());
+    assert!(validate_const("u8", r#"{"Const":{"U8":255}}"#).is_ok());
+    assert!(validate_const("u8", r#"{"Const":{"String":"256"}}"#).is_err());
+    assert!(validate_const("Json", r#"{"Const":{"String":"{\"a\": 1}"}}"#).is_ok());
+    assert!(validate_const("Json", r#"{"Const":{"String":"{a"}}"#).is_err());
+    assert!(validate_const("Seed Phrase", r#"{"Const":{"String":"abandon abandon"}}"#).is_err());
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+/// const values referencing a secret look like "vault:<id>"
+pub const VAULT_REFERENCE_PREFIX: &str = "vault:";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const VERIFIER: &[u8] = b"space operator vault";
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub struct VaultId(pub Uuid);
+
+impl VaultId {
+    pub fn reference(&self) -> String {
+        format!("{}{}", VAULT_REFERENCE_PREFIX, self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultError {
+    Locked,
+    WrongPassword,
+    NotFound(VaultId),
+    NotText, // only text consts can be moved to the vault
+    Io(String),
+    Corrupt(String), // the file can't be read back
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Locked => write!(f, "vault is locked"),
+            VaultError::WrongPassword => write!(f, "wrong vault password"),
+            VaultError::NotFound(id) => write!(f, "secret {} not found in vault", id.0),
+            VaultError::NotText => write!(f, "only text values can be stored in the vault"),
+            VaultError::Io(e) => write!(f, "vault file error: {}", e),
+            VaultError::Corrupt(e) => write!(f, "vault file is corrupt: {}", e),
+        }
+    }
+}
+
+/// public part of a secret, safe to show in the ui
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct VaultEntry {
+    pub label: String,
+    pub kind: String, // ADDITIONAL_DATA_MARKER of the const it came from, e.g. "Seed Phrase"
+    nonce: String,    // base58
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct VaultFile {
+    salt: String, // base58
+    verifier_nonce: String,
+    verifier: String,
+    entries: HashMap<VaultId, VaultEntry>,
+}
+
+/// password protected secrets, stored next to the run logs
+///
+/// the key is derived with argon2 and entries are sealed with XChaCha20-Poly1305,
+/// the key only lives in memory until the vault is locked again
+pub struct Vault {
+    path: PathBuf,
+    file: Option<VaultFile>, // None until the vault is created
+    key: Option<[u8; 32]>,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("is_unlocked", &self.is_unlocked())
+            .finish()
+    }
+}
+
+impl Vault {
+    /// no vault until the first unlock when the file doesn't exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VaultError> {
+        let path = path.as_ref().to_owned();
+        let file = match std::fs::read(&path) {
+            Ok(bytes) => Some(
+                serde_json::from_slice(&bytes).map_err(|e| VaultError::Corrupt(e.to_string()))?,
+            ),
+            Err(_) => None,
+        };
+
+        Ok(Self {
+            path,
+            file,
+            key: None,
+        })
+    }
+
+    /// stand-in for a file that can't be read, nothing is written until it's reopened
+    pub fn unreadable(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_owned(),
+            file: None,
+            key: None,
+        }
+    }
+
+    /// reads the file again, e.g. once a corrupt one was moved away
+    pub fn reopen(&mut self) -> Result<(), VaultError> {
+        *self = Vault::open(&self.path)?;
+        Ok(())
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// first unlock creates the vault with this password
+    pub fn unlock(&mut self, password: &str) -> Result<(), VaultError> {
+        match &self.file {
+            Some(file) => {
+                let key = derive_key(password, &decode(&file.salt)?)?;
+                open(&key, &file.verifier_nonce, &file.verifier)
+                    .map_err(|_| VaultError::WrongPassword)?;
+                self.key = Some(key);
+            }
+            None => {
+                let mut salt = [0u8; SALT_LEN];
+                rand::thread_rng().fill_bytes(&mut salt);
+
+                let key = derive_key(password, &salt)?;
+                let (verifier_nonce, verifier) = seal(&key, VERIFIER);
+
+                self.file = Some(VaultFile {
+                    salt: bs58::encode(salt).into_string(),
+                    verifier_nonce,
+                    verifier,
+                    entries: HashMap::new(),
+                });
+                self.key = Some(key);
+                self.save()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn lock(&mut self) {
+        if let Some(key) = &mut self.key {
+            key.fill(0);
+        }
+        self.key = None;
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&VaultId, &VaultEntry)> {
+        self.file.iter().flat_map(|file| file.entries.iter())
+    }
+
+    pub fn add_secret(
+        &mut self,
+        label: String,
+        kind: String,
+        secret: &str,
+    ) -> Result<VaultId, VaultError> {
+        let key = self.key.ok_or(VaultError::Locked)?;
+        let (nonce, ciphertext) = seal(&key, secret.as_bytes());
+
+        let id = VaultId(Uuid::new_v4());
+        self.file.as_mut().unwrap().entries.insert(
+            id,
+            VaultEntry {
+                label,
+                kind,
+                nonce,
+                ciphertext,
+            },
+        );
+        self.save()?;
+
+        Ok(id)
+    }
+
+    pub fn remove_secret(&mut self, id: VaultId) -> Result<(), VaultError> {
+        let file = self.file.as_mut().ok_or(VaultError::NotFound(id))?;
+        file.entries.remove(&id).ok_or(VaultError::NotFound(id))?;
+        self.save()
+    }
+
+    pub fn secret(&self, id: VaultId) -> Result<String, VaultError> {
+        let key = self.key.ok_or(VaultError::Locked)?;
+        let entry = self
+            .file
+            .as_ref()
+            .and_then(|file| file.entries.get(&id))
+            .ok_or(VaultError::NotFound(id))?;
+
+        let secret = open(&key, &entry.nonce, &entry.ciphertext)?;
+        String::from_utf8(secret).map_err(|_| VaultError::Corrupt("secret isn't text".to_owned()))
+    }
+
+    /// replace every "vault:<id>" string in a value with its secret
+    pub fn resolve(&self, value: &mut JsonValue) -> Result<bool, VaultError> {
+        let mut is_resolved = false;
+
+        match value {
+            JsonValue::String(text) => {
+                if let Some(id) = parse_reference(text) {
+                    *text = self.secret(id)?;
+                    is_resolved = true;
+                }
+            }
+            JsonValue::Array(values) => {
+                for value in values {
+                    is_resolved |= self.resolve(value)?;
+                }
+            }
+            JsonValue::Object(map) => {
+                for value in map.values_mut() {
+                    is_resolved |= self.resolve(value)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(is_resolved)
+    }
+
+    fn save(&self) -> Result<(), VaultError> {
+        let bytes = serde_json::to_vec_pretty(self.file.as_ref().unwrap()).unwrap();
+        std::fs::write(&self.path, bytes).map_err(|e| VaultError::Io(e.to_string()))
+    }
+}
+
+// start of an encrypted file, followed by the salt, the nonce and the ciphertext
+const ENCRYPTED_FILE_MAGIC: &[u8] = b"SPACE OPERATOR ENCRYPTED 1\n";
+
+/// file sealed with a passphrase, e.g. an encrypted export
+///
+/// written as raw bytes, exports of large graphs can be megabytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedFile {
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedFile {
+    pub fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<Self, VaultError> {
+        let mut salt = [0u8; SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        let key = derive_key(passphrase, &salt)?;
+        let (nonce, ciphertext) = seal_bytes(&key, plaintext);
+
+        Ok(Self {
+            salt,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, VaultError> {
+        let key = derive_key(passphrase, &self.salt)?;
+        open_bytes(&key, &self.nonce, &self.ciphertext)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = ENCRYPTED_FILE_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// None when the bytes aren't an encrypted file, e.g. a plain export
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.strip_prefix(ENCRYPTED_FILE_MAGIC)?;
+        if bytes.len() < SALT_LEN + NONCE_LEN {
+            return None;
+        }
+        let (salt, bytes) = bytes.split_at(SALT_LEN);
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+
+        Some(Self {
+            salt: salt.try_into().unwrap(),
+            nonce: nonce.try_into().unwrap(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+}
+
+pub fn parse_reference(text: &str) -> Option<VaultId> {
+    text.strip_prefix(VAULT_REFERENCE_PREFIX)
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .map(VaultId)
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<[u8; 32], VaultError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| VaultError::Io(e.to_string()))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plaintext: &[u8]) -> (String, String) {
+    let (nonce, ciphertext) = seal_bytes(key, plaintext);
+    (
+        bs58::encode(nonce).into_string(),
+        bs58::encode(ciphertext).into_string(),
+    )
+}
+
+fn seal_bytes(key: &[u8; 32], plaintext: &[u8]) -> ([u8; NONCE_LEN], Vec<u8>) {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .unwrap();
+
+    (nonce, ciphertext)
+}
+
+fn open(key: &[u8; 32], nonce: &str, ciphertext: &str) -> Result<Vec<u8>, VaultError> {
+    open_bytes(key, &decode(nonce)?, &decode(ciphertext)?)
+}
+
+fn open_bytes(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, VaultError> {
+    if nonce.len() != NONCE_LEN {
+        return Err(VaultError::WrongPassword);
+    }
+
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| VaultError::WrongPassword)
+}
+
+fn decode(text: &str) -> Result<Vec<u8>, VaultError> {
+    bs58::decode(text)
+        .into_vec()
+        .map_err(|e| VaultError::Corrupt(e.to_string()))
+}
+
+#[test]
+fn vault_round_trip() {
+    let path = std::env::temp_dir().join(format!("vault-{}.json", Uuid::new_v4()));
+
+    let mut vault = Vault::open(&path).unwrap();
+    vault.unlock("password").unwrap();
+    let id = vault
+        .add_secret("payer".into(), "Seed Phrase".into(), "secret words")
+        .unwrap();
+    vault.lock();
+    assert_eq!(vault.secret(id), Err(VaultError::Locked));
+
+    let mut vault = Vault::open(&path).unwrap();
+    assert_eq!(vault.unlock("wrong"), Err(VaultError::WrongPassword));
+    vault.unlock("password").unwrap();
+
+    let mut value = serde_json::json!({ "Const": { "String": id.reference() } });
+    assert!(vault.resolve(&mut value).unwrap());
+    assert_eq!(
+        value,
+        serde_json::json!({ "Const": { "String": "secret words" } })
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn vault_corrupt_file() {
+    let path = std::env::temp_dir().join(format!("vault-{}.json", Uuid::new_v4()));
+
+    std::fs::write(&path, b"{ not json").unwrap();
+    assert!(matches!(Vault::open(&path), Err(VaultError::Corrupt(_))));
+
+    // nothing is read until the file is fixed or moved away
+    let mut vault = Vault::unreadable(&path);
+    assert!(vault.reopen().is_err());
+    std::fs::remove_file(&path).unwrap();
+    assert!(vault.reopen().is_ok());
+
+    let file = VaultFile {
+        salt: "0OIl".to_owned(), // not base58
+        ..VaultFile::default()
+    };
+    std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+    let mut vault = Vault::open(&path).unwrap();
+    assert!(matches!(
+        vault.unlock("password"),
+        Err(VaultError::Corrupt(_))
+    ));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn encrypted_file_round_trip() {
+    let plaintext = vec![7u8; 100_000];
+    let bytes = EncryptedFile::encrypt("passphrase", &plaintext)
+        .unwrap()
+        .to_bytes();
+
+    let encrypted = EncryptedFile::from_bytes(&bytes).unwrap();
+    assert_eq!(encrypted.decrypt("passphrase").unwrap(), plaintext);
+    assert_eq!(encrypted.decrypt("wrong"), Err(VaultError::WrongPassword));
+    assert!(EncryptedFile::from_bytes(b"{\"nodes\":[]}").is_none());
+}
@@ -16,7 +16,13 @@ use crate::model::{GraphEntry, SolanaNet};
     EdgeView,
     GraphEntry,
     BookmarkView,
-    DebugData
+    DebugData,
+    VaultView,
+    AddressEntryView,
+    SolanaEndpointView,
+    WalletKeypairView,
+    ActiveRunView,
+    FlowInputView
 )]
 #[rid::enums(SolanaNet)]
 pub struct View {
@@ -24,6 +30,7 @@ pub struct View {
     pub nodes: HashMap<String, NodeView>,
     pub flow_edges: HashMap<String, EdgeView>,
     pub selected_node_ids: Vec<String>,
+    pub selected_edge_ids: Vec<String>,
     pub selected_command_ids: Vec<String>,
     pub selection: Selection, // TODO Implement
     pub command: Command,     // not used
@@ -34,7 +41,14 @@ pub struct View {
     pub transform_screenshot: Camera,
     pub bookmarks: HashMap<String, BookmarkView>,
     pub solana_net: SolanaNet,
+    pub solana_endpoint: SolanaEndpointView, // empty for public clusters
     pub ui_state_debug: DebugData,
+    pub vault: VaultView,
+    pub address_book: Vec<AddressEntryView>,
+    pub wallet: Vec<WalletKeypairView>, // keypairs of the flow wallet graph
+    pub active_runs: Vec<ActiveRunView>, // deployed graphs, including the ones not opened
+    pub flow_inputs: Vec<FlowInputView>, // parameters given to Msg::Deploy
+    pub watch_trigger: String,          // WatchTriggerConfig json, empty when not watching
 }
 
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
@@ -58,7 +72,9 @@ pub struct Ratio {
 pub struct LastViewChanges {
     pub changed_nodes_ids: HashMap<String, NodeChange>, /*NodeChangeKind*/
     pub changed_flow_edges_ids: Vec<String>,
+    pub replaced_flow_edges_ids: Vec<String>, // removed by connecting an input again, to the same output or another one
     pub is_selected_node_ids_changed: bool,
+    pub is_selected_edge_ids_changed: bool,
     pub is_selection_changed: bool,
     pub is_command_changed: bool,
     pub is_text_commands_changed: bool,
@@ -68,6 +84,12 @@ pub struct LastViewChanges {
     pub is_transform_screenshot_changed: bool,
     pub is_graph_changed: bool,
     pub is_bookmark_changed: bool,
+    pub is_vault_changed: bool,
+    pub is_address_book_changed: bool,
+    pub is_wallet_changed: bool,
+    pub is_active_runs_changed: bool,
+    pub is_flow_inputs_changed: bool,
+    pub is_watch_trigger_changed: bool,
 }
 
 impl From<i64> for Ratio {
@@ -290,6 +312,40 @@ pub fn commands_view_map() -> HashMap<&'static str, &'static dyn DynCommandView>
         .collect()
 }
 
+const VALUE_LABEL_LEN: usize = 24;
+
+/// short text of a run value, { "U64": 5 } -> 5
+pub fn value_label(value: &serde_json::Value) -> String {
+    let value = match value {
+        serde_json::Value::Object(map) if map.len() == 1 => map.values().next().unwrap(),
+        value => value,
+    };
+    let text = match value {
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+
+    if text.chars().count() <= VALUE_LABEL_LEN {
+        return text;
+    }
+    let mut label: String = text.chars().take(VALUE_LABEL_LEN - 1).collect();
+    label.push('…');
+    label
+}
+
+#[test]
+fn value_label_shortens_values() {
+    use serde_json::json;
+
+    assert_eq!(value_label(&json!({ "U64": 5 })), "5");
+    assert_eq!(value_label(&json!({ "String": "hello" })), "hello");
+    assert_eq!(
+        value_label(&json!({ "Pubkey": "11111111111111111111111111111111" })),
+        "11111111111111111111111…"
+    );
+    assert_eq!(value_label(&json!([1, 2])), "[1,2]");
+}
+
 #[test]
 fn commands_equal_view_commands() {
     assert_eq!(COMMANDS.len(), VIEW_COMMANDS.len());
@@ -332,14 +388,10 @@ pub fn generate_default_text_commands() -> Vec<WidgetTextCommand> {
                 })
                 .collect(),
             description: command.description().to_owned(),
-            availability: command
-                .availability()
+            availability: SolanaNet::ALL
                 .iter()
-                .map(|solana_net| match solana_net {
-                    SolanaNet::Devnet => "devnet".to_string(),
-                    SolanaNet::Testnet => "testnet".to_string(),
-                    SolanaNet::Mainnet => "mainnet".to_string(),
-                })
+                .filter(|solana_net| command.is_available(**solana_net))
+                .map(|solana_net| solana_net.name().to_string())
                 .collect(),
         })
         .collect()
@@ -352,6 +404,7 @@ impl Default for View {
             nodes: HashMap::default(),
             flow_edges: HashMap::default(),
             selected_node_ids: Vec::default(),
+            selected_edge_ids: Vec::default(),
             selected_command_ids: Vec::default(),
             selection: Selection::default(),
             command: Default::default(),
@@ -362,7 +415,14 @@ impl Default for View {
             transform_screenshot: Camera::default(),
             bookmarks: HashMap::default(),
             solana_net: SolanaNet::Devnet,
+            solana_endpoint: SolanaEndpointView::default(),
             ui_state_debug: DebugData::default(),
+            vault: VaultView::default(),
+            address_book: Vec::default(),
+            wallet: Vec::default(),
+            active_runs: Vec::default(),
+            flow_inputs: Vec::default(),
+            watch_trigger: String::new(),
         }
     }
 }
@@ -446,6 +506,16 @@ pub struct NodeView {
     pub passthrough: bool,
     pub default_value: String,
     pub has_default: bool,
+    pub literal_value: String, // inline value of an input, ignored when connected
+    pub has_literal: bool,
+    pub is_secret: bool,                // const redacted on export
+    pub network_values: String, // { "mainnet": const text, ... }, empty when not per network
+    pub is_missing_network_value: bool, // no value for the selected network
+    pub is_response: bool,      // print node answering http run_sync requests
+    pub is_breakpoint: bool,
+    pub input_values: String, // { input label: value } while paused before the node
+    pub output_values: String, // { output label: value } given by the latest run
+    pub port_value: String,   // json received by an input or given by an output in the latest run
 }
 
 #[rid::model]
@@ -457,6 +527,7 @@ pub enum RunStateView {
     Success,
     NotRunning,
     Canceled,
+    Paused, // before a breakpoint, see Msg::Continue and Msg::Step
 }
 
 #[rid::model]
@@ -508,6 +579,7 @@ pub enum NodeViewType {
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[rid::model]
+#[rid::structs(EdgePoint)]
 #[rid::enums(ViewEdgeType)]
 pub struct EdgeView {
     pub from: String,
@@ -517,6 +589,15 @@ pub struct EdgeView {
     pub from_coords_y: i64,
     pub to_coords_x: i64,
     pub to_coords_y: i64,
+    pub points: Vec<EdgePoint>, // polyline including both ends, empty for child edges
+    pub label: String,          // value passed along a flow edge by the latest run, shortened
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[rid::model]
+pub struct EdgePoint {
+    pub x: i64,
+    pub y: i64,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -533,3 +614,64 @@ pub struct BookmarkView {
     pub name: String,
     pub nodes: Vec<String>,
 }
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[rid::model]
+#[rid::structs(VaultEntryView)]
+pub struct VaultView {
+    pub is_unlocked: bool,
+    pub entries: Vec<VaultEntryView>, // secrets themselves are never sent to the ui
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[rid::model]
+pub struct VaultEntryView {
+    pub id: String,
+    pub label: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[rid::model]
+pub struct AddressEntryView {
+    pub name: String,
+    pub pubkey: String,
+    pub networks: String, // { "mainnet": pubkey, ... }
+    pub notes: String,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+#[rid::model]
+pub struct SolanaEndpointView {
+    pub rpc_url: String,
+    pub ws_url: String,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[rid::model]
+pub struct WalletKeypairView {
+    pub node_id: String,
+    pub label: String,
+    pub pubkey: String,
+    pub is_default_payer: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[rid::model]
+#[rid::enums(RunStateView)]
+pub struct ActiveRunView {
+    pub run_id: String,
+    pub graph_id: String,
+    pub graph_name: String,
+    pub network: String,
+    pub state: RunStateView,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[rid::model]
+pub struct FlowInputView {
+    pub node_id: String,
+    pub name: String,
+    pub kind: String,
+    pub default_value: String, // json, empty when the input is required
+}
@@ -0,0 +1,199 @@
+use ed25519_dalek_bip32::ed25519_dalek::{PublicKey, SecretKey};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+/// properties of a keypair node imported into an empty wallet graph
+///
+/// nodes written by the runner are recognized by their values, see `WalletSchema`
+pub const WALLET_KEYPAIR: &str = "keypair"; // base58 of the 64 bytes keypair
+pub const WALLET_PUBKEY: &str = "pubkey";
+pub const WALLET_LABEL_MARKER: &str = "WALLET_LABEL_MARKER";
+pub const DEFAULT_PAYER_MARKER: &str = "DEFAULT_PAYER_MARKER";
+
+/// keypair listed in the ui, the secret stays in the db
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WalletKeypair {
+    pub node_id: String,
+    pub label: String,
+    pub pubkey: String,
+    pub is_default_payer: bool,
+}
+
+impl WalletKeypair {
+    pub fn from_properties(
+        node_id: String,
+        properties: &serde_json::Map<String, JsonValue>,
+    ) -> Option<Self> {
+        let (_, bytes) = find_keypair(properties)?;
+        let pubkey = keypair_pubkey(&bytes).ok()?;
+
+        let label = properties
+            .get(WALLET_LABEL_MARKER)
+            .or_else(|| properties.get("name"))
+            .and_then(JsonValue::as_str)
+            .unwrap_or_default()
+            .to_owned();
+
+        Some(Self {
+            node_id,
+            label,
+            pubkey,
+            is_default_payer: properties.contains_key(DEFAULT_PAYER_MARKER),
+        })
+    }
+}
+
+/// how the keypair nodes of a wallet store their keypair and pubkey
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletSchema {
+    pub keypair: String,
+    pub pubkey: Option<String>,
+    pub json_array: bool, // keypair as a json array of bytes instead of base58
+}
+
+impl Default for WalletSchema {
+    fn default() -> Self {
+        Self {
+            keypair: WALLET_KEYPAIR.to_owned(),
+            pubkey: Some(WALLET_PUBKEY.to_owned()),
+            json_array: false,
+        }
+    }
+}
+
+impl WalletSchema {
+    /// schema of an existing keypair node
+    pub fn from_properties(properties: &serde_json::Map<String, JsonValue>) -> Option<Self> {
+        let (keypair, bytes) = find_keypair(properties)?;
+        let pubkey = keypair_pubkey(&bytes).ok()?;
+
+        Some(Self {
+            json_array: properties[&keypair].is_array(),
+            keypair,
+            pubkey: properties
+                .iter()
+                .find(|(_, value)| value.as_str() == Some(pubkey.as_str()))
+                .map(|(name, _)| name.clone()),
+        })
+    }
+
+    pub fn properties(&self, bytes: &[u8]) -> serde_json::Map<String, JsonValue> {
+        let mut properties = serde_json::Map::new();
+
+        let keypair = match self.json_array {
+            true => serde_json::to_value(bytes).unwrap(),
+            false => JsonValue::String(bs58::encode(bytes).into_string()),
+        };
+        properties.insert(self.keypair.clone(), keypair);
+
+        if let Some(pubkey) = &self.pubkey {
+            properties.insert(
+                pubkey.clone(),
+                JsonValue::String(bs58::encode(&bytes[32..]).into_string()),
+            );
+        }
+
+        properties
+    }
+}
+
+/// name and bytes of the property holding a valid keypair,
+/// as base58 or as a json array of 64 bytes
+pub fn find_keypair(properties: &serde_json::Map<String, JsonValue>) -> Option<(String, Vec<u8>)> {
+    properties
+        .get_key_value(WALLET_KEYPAIR)
+        .into_iter()
+        .chain(properties.iter())
+        .find_map(|(name, value)| keypair_bytes(value).map(|bytes| (name.clone(), bytes)))
+}
+
+fn keypair_bytes(value: &JsonValue) -> Option<Vec<u8>> {
+    let bytes: Vec<u8> = match value {
+        JsonValue::String(keypair) => bs58::decode(keypair.trim()).into_vec().ok()?,
+        JsonValue::Array(_) => serde_json::from_value(value.clone()).ok()?,
+        _ => return None,
+    };
+    keypair_pubkey(&bytes).ok()?;
+    Some(bytes)
+}
+
+/// keypair file written by `solana-keygen`, a json array of 64 bytes
+pub fn parse_cli_keypair(json: &str) -> Result<Vec<u8>, String> {
+    let bytes: Vec<u8> =
+        serde_json::from_str(json).map_err(|e| format!("invalid keypair file: {}", e))?;
+    keypair_pubkey(&bytes)?;
+    Ok(bytes)
+}
+
+pub fn to_cli_keypair(bytes: &[u8]) -> String {
+    serde_json::to_string(bytes).unwrap()
+}
+
+/// base58 pubkey, checking that it matches the secret half
+pub fn keypair_pubkey(bytes: &[u8]) -> Result<String, String> {
+    if bytes.len() != 64 {
+        return Err(format!("expected 64 bytes, got {}", bytes.len()));
+    }
+
+    let secret = SecretKey::from_bytes(&bytes[..32]).map_err(|e| e.to_string())?;
+    let public: PublicKey = (&secret).into();
+    if public.as_bytes() != &bytes[32..] {
+        return Err("public key doesn't match the secret key".to_owned());
+    }
+
+    Ok(bs58::encode(&bytes[32..]).into_string())
+}
+
+#[test]
+fn cli_keypair_round_trip() {
+    let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+    let public: PublicKey = (&secret).into();
+    let mut bytes = secret.as_bytes().to_vec();
+    bytes.extend_from_slice(public.as_bytes());
+
+    let json = to_cli_keypair(&bytes);
+    assert_eq!(parse_cli_keypair(&json).unwrap(), bytes);
+
+    bytes[40] ^= 1;
+    assert!(parse_cli_keypair(&to_cli_keypair(&bytes)).is_err());
+    assert!(parse_cli_keypair("[1, 2, 3]").is_err());
+}
+
+#[test]
+fn wallet_keypair_by_value() {
+    let secret = SecretKey::from_bytes(&[9u8; 32]).unwrap();
+    let public: PublicKey = (&secret).into();
+    let mut bytes = secret.as_bytes().to_vec();
+    bytes.extend_from_slice(public.as_bytes());
+    let pubkey = bs58::encode(public.as_bytes()).into_string();
+
+    // names the runner might use, the keypair is found by its value
+    let properties = match serde_json::json!({
+        "name": "payer",
+        "secret_key": bytes,
+        "public_key": pubkey,
+    }) {
+        JsonValue::Object(properties) => properties,
+        _ => unreachable!(),
+    };
+
+    let keypair = WalletKeypair::from_properties("node".into(), &properties).unwrap();
+    assert_eq!(keypair.pubkey, pubkey);
+    assert_eq!(keypair.label, "payer");
+
+    let schema = WalletSchema::from_properties(&properties).unwrap();
+    assert_eq!(schema.keypair, "secret_key");
+    assert_eq!(schema.pubkey.as_deref(), Some("public_key"));
+    assert!(schema.json_array);
+    assert_eq!(find_keypair(&schema.properties(&bytes)).unwrap().1, bytes);
+
+    let default = WalletSchema::default().properties(&bytes);
+    assert_eq!(
+        find_keypair(&default).unwrap(),
+        (WALLET_KEYPAIR.to_owned(), bytes)
+    );
+
+    let mut broken = properties.clone();
+    broken.insert("secret_key".into(), serde_json::json!([1, 2, 3]));
+    assert!(WalletKeypair::from_properties("node".into(), &broken).is_none());
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::flow_context::RunRequest;
+use crate::http_trigger::TriggerContext;
+use crate::model::GraphId;
+
+type Properties = serde_json::Map<String, JsonValue>;
+
+/// WatchTriggerConfig of a graph, on the graph node
+pub const WATCH_TRIGGER_MARKER: &str = "WATCH_TRIGGER_MARKER";
+
+/// node giving the path and the contents of the file to the run, see WatchTriggerCommand
+pub const WATCH_TRIGGER_COMMAND: &str = "watch_trigger";
+
+// how often pending files are checked for the end of their debounce
+const DEBOUNCE_TICK: tokio::time::Duration = tokio::time::Duration::from_millis(100);
+
+/// starts a run per new or changed file of a directory,
+/// the path and the contents are the outputs of the watch_trigger node of the graph
+///
+/// binary files, e.g. images, only give their path
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WatchTriggerConfig {
+    pub directory: String,
+    #[serde(default = "default_glob")]
+    pub glob: String, // matched against the file name, e.g. "*.csv"
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64, // a file is sent once it stopped changing for this long
+}
+
+fn default_glob() -> String {
+    "*".to_owned()
+}
+
+fn default_debounce_ms() -> u64 {
+    500
+}
+
+impl WatchTriggerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !Path::new(&self.directory).is_dir() {
+            return Err(format!("{} is not a directory", self.directory));
+        }
+        glob::Pattern::new(&self.glob).map_err(|e| format!("invalid glob: {}", e))?;
+        Ok(())
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        let file_name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(file_name) => file_name,
+            None => return false,
+        };
+
+        path.is_file()
+            && glob::Pattern::new(&self.glob)
+                .map(|pattern| pattern.matches(file_name))
+                .unwrap_or(false)
+    }
+
+    /// outputs of the watch_trigger node in the run for the file,
+    /// { output label: json of a sunshine_solana::Value }
+    pub fn trigger_outputs(&self, path: &Path) -> Properties {
+        let mut outputs = Properties::new();
+
+        outputs.insert("path".into(), json!({ "String": path.to_string_lossy() }));
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            outputs.insert("contents".into(), json!({ "String": contents }));
+        }
+
+        outputs
+    }
+}
+
+/// runs the flow for each file event, until shutdown
+pub async fn watch(
+    graph_id: GraphId,
+    config: WatchTriggerConfig,
+    ctx: TriggerContext,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+
+    let mut watcher =
+        match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                    for path in event.paths {
+                        tx.send(path).ok();
+                    }
+                }
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                eprintln!("error while watching {}: {}", config.directory, e);
+                return;
+            }
+        };
+
+    if let Err(e) = watcher.watch(Path::new(&config.directory), RecursiveMode::NonRecursive) {
+        eprintln!("error while watching {}: {}", config.directory, e);
+        return;
+    }
+
+    let debounce = tokio::time::Duration::from_millis(config.debounce_ms);
+    let mut pending: HashMap<PathBuf, tokio::time::Instant> = HashMap::new();
+    let mut interval = tokio::time::interval(DEBOUNCE_TICK);
+
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => return,
+            path = rx.recv() => match path {
+                Some(path) => {
+                    pending.insert(path, tokio::time::Instant::now());
+                }
+                None => return,
+            },
+            _ = interval.tick() => {
+                let now = tokio::time::Instant::now();
+                let ready: Vec<PathBuf> = pending
+                    .iter()
+                    .filter(|(_, changed_at)| now.duration_since(**changed_at) >= debounce)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+
+                for path in ready {
+                    pending.remove(&path);
+                    if !config.matches(&path) {
+                        continue;
+                    }
+
+                    let sender = ctx.sender.clone();
+                    let request = RunRequest {
+                        flow_id: graph_id,
+                        input_values: Properties::new(),
+                        trigger_outputs: config.trigger_outputs(&path),
+                        unattended: true,
+                        session: None,
+                        plan: None,
+                    };
+                    tokio::spawn(async move {
+                        if let Err(e) = sender.deploy(request).await {
+                            eprintln!("error while running flow for {}: {}", path.display(), e)
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn watch_trigger_filters_files() {
+    let directory = std::env::temp_dir().join(format!("watch-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir(&directory).unwrap();
+    std::fs::write(directory.join("mints.csv"), "name,uri\n").unwrap();
+    std::fs::write(directory.join("image.png"), [0xff, 0xd8, 0xff]).unwrap();
+
+    let config: WatchTriggerConfig = serde_json::from_value(serde_json::json!({
+        "directory": directory.to_string_lossy(),
+        "glob": "*.csv",
+    }))
+    .unwrap();
+    assert!(config.validate().is_ok());
+    assert!(config.matches(&directory.join("mints.csv")));
+    assert!(!config.matches(&directory.join("image.png")));
+
+    // each file gets its own values, runs of files changed together are separate
+    let outputs = config.trigger_outputs(&directory.join("mints.csv"));
+    assert_eq!(outputs["contents"], json!({ "String": "name,uri\n" }));
+    assert_eq!(
+        outputs["path"],
+        json!({ "String": directory.join("mints.csv").to_string_lossy() })
+    );
+    let outputs = config.trigger_outputs(&directory.join("image.png"));
+    assert!(!outputs.contains_key("contents"));
+    assert!(outputs.contains_key("path"));
+
+    std::fs::remove_dir_all(directory).unwrap();
+}